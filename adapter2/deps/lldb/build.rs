@@ -1,14 +1,202 @@
 extern crate cpp_build;
 
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
 fn main() {
-    cpp_build::Config::new().include("include").build("src/lldb.rs");
+    println!("cargo:rerun-if-env-changed=LLDB_ROOT");
+    println!("cargo:rerun-if-env-changed=LLDB_PACKAGE");
+
+    let lldb = locate_lldb_override()
+        .or_else(locate_rust_sysroot_lldb)
+        .or_else(locate_system_lldb);
+
+    detect_lldb_api_version(&lldb);
+
+    let mut config = cpp_build::Config::new();
+    config.include("include");
+    if let Some(ref lldb) = lldb {
+        if let Some(ref include) = lldb.include_dir {
+            config.include(include);
+        }
+    }
+    config.build("src/lldb.rs");
+
+    match lldb {
+        Some(LldbLocation::Library { lib_dir, lib_name, .. }) => {
+            println!("cargo:rustc-link-search=native={}", lib_dir.display());
+            println!("cargo:rustc-link-lib=dylib={}", lib_name);
+        }
+        Some(LldbLocation::Framework { search_dir }) => {
+            println!("cargo:rustc-link-search=framework={}", search_dir.display());
+            println!("cargo:rustc-link-lib=framework=LLDB");
+        }
+        None => {}
+    }
+}
+
+enum LldbLocation {
+    Library {
+        lib_dir: PathBuf,
+        lib_name: String,
+        include_dir: Option<PathBuf>,
+        bin_dir: Option<PathBuf>,
+    },
+    Framework {
+        search_dir: PathBuf,
+    },
+}
+
+// `LLDB_ROOT` (an installed LLDB tree) or `LLDB_PACKAGE` (an unpacked LLDB release archive)
+// lets the user override autodetection entirely. On Linux and Windows there is no framework
+// fallback, so without an override the build depends on the sysroot copy being present.
+fn locate_lldb_override() -> Option<LldbLocation> {
+    let root = env::var("LLDB_ROOT").or_else(|_| env::var("LLDB_PACKAGE")).ok()?;
+    let root = Path::new(&root);
+
+    let lib_dir = if root.join("lib").is_dir() { root.join("lib") } else { root.join("bin") };
+    let include_dir = root.join("include");
+    let bin_dir = root.join("bin");
+
+    let lib_name = find_lldb_lib(&lib_dir).unwrap_or_else(|| "lldb".to_owned());
+
+    Some(LldbLocation::Library {
+        lib_dir,
+        lib_name,
+        include_dir: if include_dir.is_dir() { Some(include_dir) } else { None },
+        bin_dir: if bin_dir.is_dir() { Some(bin_dir) } else { None },
+    })
+}
+
+// `rustc --print sysroot` points at a toolchain root that, for the official Rust distribution,
+// contains a copy of LLDB with the Rust language plugin built in, under
+// `lib/rustlib/<target>/{bin,lib}`. Linking against that one instead of the stock system LLDB
+// gives us Rust-aware pretty-printing and type summaries for free.
+fn locate_rust_sysroot_lldb() -> Option<LldbLocation> {
+    let sysroot = rustc_sysroot()?;
+    let target = env::var("TARGET").ok()?;
+    let rustlib_dir = Path::new(&sysroot).join("lib").join("rustlib").join(&target);
+
+    let lib_dir = rustlib_dir.join("lib");
+    if !lib_dir.is_dir() {
+        return None;
+    }
+
+    let lib_name = find_lldb_lib(&lib_dir)?;
+    let bin_dir = rustlib_dir.join("bin");
+
+    Some(LldbLocation::Library {
+        lib_dir,
+        lib_name,
+        include_dir: None,
+        bin_dir: if bin_dir.is_dir() { Some(bin_dir) } else { None },
+    })
+}
+
+// macOS ships a stock LLDB as a private framework, with no Rust plugin, but good enough as a
+// last resort if neither an override nor the sysroot copy is available.
+fn locate_system_lldb() -> Option<LldbLocation> {
+    if cfg!(target_os = "macos") {
+        Some(LldbLocation::Framework {
+            search_dir: PathBuf::from("/Library/Developer/CommandLineTools/Library/PrivateFrameworks"),
+        })
+    } else {
+        None
+    }
+}
+
+// The SB API surface differs across major LLDB versions (symbols get added and occasionally
+// removed), so the `cpp!` blocks in `src/lldb.rs` need to know which version they're compiling
+// against. We emit `cargo:rustc-cfg=lldb_api="NN"` (NN = major version) for every version from
+// the detected one down to our oldest supported baseline, so `#[cfg(lldb_api = "NN")]` reads as
+// "available from version NN onward" rather than "exactly version NN".
+fn detect_lldb_api_version(lldb: &Option<LldbLocation>) -> Option<u32> {
+    const OLDEST_SUPPORTED: u32 = 6;
+
+    let version = version_from_lldb_binary(lldb)
+        .or_else(|| version_from_framework_plist(lldb))
+        .unwrap_or(OLDEST_SUPPORTED);
+
+    for v in OLDEST_SUPPORTED..=version {
+        println!("cargo:rustc-cfg=lldb_api=\"{}\"", v);
+    }
+    Some(version)
+}
+
+// Probes the `lldb` binary that sits next to the library we're actually linking against, rather
+// than whatever `lldb` happens to be first on `PATH` -- otherwise the detected API version can
+// silently mismatch the linked library and mis-gate the `#[cfg(lldb_api = "NN")]` branches.
+fn version_from_lldb_binary(lldb: &Option<LldbLocation>) -> Option<u32> {
+    let bin_dir = match lldb {
+        Some(LldbLocation::Library { bin_dir: Some(bin_dir), .. }) => bin_dir,
+        _ => return None,
+    };
+    let exe_name = if cfg!(target_os = "windows") { "lldb.exe" } else { "lldb" };
+    let output = Command::new(bin_dir.join(exe_name)).arg("--version").output().ok()?;
+    let text = String::from_utf8_lossy(&output.stdout);
+    parse_lldb_version(&text)
+}
+
+// macOS's bundled LLDB framework carries its version in Info.plist rather than exposing a CLI.
+fn version_from_framework_plist(lldb: &Option<LldbLocation>) -> Option<u32> {
+    let search_dir = match lldb {
+        Some(LldbLocation::Framework { search_dir }) => search_dir,
+        _ => return None,
+    };
+    let plist_path = search_dir.join("LLDB.framework/Resources/Info.plist");
+    let contents = std::fs::read_to_string(plist_path).ok()?;
+    // Looks for <key>CFBundleVersion</key><string>1400.0.32</string> and takes the leading
+    // component (Apple's LLDB versions don't line up with upstream major versions, but the
+    // leading digits still move in lockstep with the SB API surface).
+    let marker = "CFBundleVersion";
+    let idx = contents.find(marker)?;
+    let tail = &contents[idx..];
+    let string_start = tail.find("<string>")? + "<string>".len();
+    let string_end = tail[string_start..].find("</string>")? + string_start;
+    let version_str = &tail[string_start..string_end];
+    let major: &str = version_str.split('.').next()?;
+    major.parse().ok().map(|v: u32| v / 100)
+}
+
+// "lldb version 14.0.0" / "lldb-1400.0.32.6" / "LLDB-1200.0.44.2" -> 14 / 14 / 12
+fn parse_lldb_version(text: &str) -> Option<u32> {
+    let digits: String = text.chars().skip_while(|c| !c.is_ascii_digit()).take_while(|c| c.is_ascii_digit() || *c == '.').collect();
+    let first_component: &str = digits.split('.').next()?;
+    let value: u32 = first_component.parse().ok()?;
+    Some(if value > 100 { value / 100 } else { value })
+}
+
+fn rustc_sysroot() -> Option<String> {
+    let rustc = env::var("RUSTC").unwrap_or_else(|_| "rustc".to_owned());
+    let output = Command::new(rustc).args(&["--print", "sysroot"]).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_owned())
+}
+
+fn find_lldb_lib(lib_dir: &Path) -> Option<String> {
+    let entries = lib_dir.read_dir().ok()?;
+    for entry in entries.filter_map(|e| e.ok()) {
+        let file_name = entry.file_name();
+        let file_name = file_name.to_string_lossy();
+        if let Some(name) = lib_name_from_file_name(&file_name) {
+            return Some(name);
+        }
+    }
+    None
+}
 
-    #[cfg(os = "macos")]
+// liblldb.so[.N] on Linux, liblldb.dylib on macOS, liblldb.dll / liblldb.lib (import lib) on Windows.
+fn lib_name_from_file_name(file_name: &str) -> Option<String> {
+    if file_name.starts_with("liblldb.so")
+        || file_name == "liblldb.dylib"
+        || file_name == "liblldb.dll"
+        || file_name == "liblldb.lib"
     {
-        println!(
-            "cargo:rustc-link-search=framework={}",
-            "/Library/Developer/CommandLineTools/Library/PrivateFrameworks"
-        );
-        println!("cargo:rustc-link-lib=framework={}", "LLDB");
+        Some("lldb".to_owned())
+    } else {
+        None
     }
 }
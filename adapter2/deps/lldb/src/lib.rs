@@ -0,0 +1,10 @@
+//! Hand-written Rust bindings for the subset of the LLDB C++ SB API the adapter needs.
+//! The actual glue is generated by `cpp_build` from the `cpp!`/`cpp_class!` blocks in `lldb.rs`.
+
+#[macro_use]
+extern crate cpp;
+
+mod lldb;
+pub mod session;
+
+pub use crate::lldb::*;
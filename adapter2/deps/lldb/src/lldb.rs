@@ -0,0 +1,241 @@
+// Rust bindings for the LLDB SB (Stable Binding) C++ API, generated via the `cpp` crate.
+// Only the subset of the API actually used by the adapter is exposed here; it grows on demand
+// as `debug_session.rs` needs more of the SB surface.
+
+use std::ffi::CStr;
+use std::os::raw::c_char;
+
+cpp! {{
+    #include <lldb/API/LLDBAPI.h>
+    using namespace lldb;
+}}
+
+pub type BreakpointID = u32;
+pub type ThreadID = u64;
+
+#[repr(u32)]
+#[derive(Clone, Copy)]
+pub enum LaunchFlag {
+    StopAtEntry = 1 << 2,
+}
+
+cpp_class!(pub unsafe struct SBDebugger as "SBDebugger");
+impl SBDebugger {
+    pub fn initialize() {
+        cpp!(unsafe [] { SBDebugger::Initialize(); })
+    }
+    pub fn terminate() {
+        cpp!(unsafe [] { SBDebugger::Terminate(); })
+    }
+    pub fn create(source_init_files: bool) -> SBDebugger {
+        cpp!(unsafe [source_init_files as "bool"] -> SBDebugger as "SBDebugger" {
+            return SBDebugger::Create(source_init_files);
+        })
+    }
+    pub fn set_async(&self, async_: bool) {
+        cpp!(unsafe [self as "SBDebugger*", async_ as "bool"] { self->SetAsync(async_); })
+    }
+    pub fn command_interpreter(&self) -> SBCommandInterpreter {
+        cpp!(unsafe [self as "SBDebugger*"] -> SBCommandInterpreter as "SBCommandInterpreter" {
+            return self->GetCommandInterpreter();
+        })
+    }
+    pub fn selected_target(&self) -> SBTarget {
+        cpp!(unsafe [self as "SBDebugger*"] -> SBTarget as "SBTarget" { return self->GetSelectedTarget(); })
+    }
+    pub fn create_target(
+        &self, executable: &str, target_triple: Option<&str>, platform_name: Option<&str>, add_dependent_modules: bool,
+    ) -> Result<SBTarget, SBError> {
+        let executable = executable.as_ptr() as *const c_char;
+        cpp!(unsafe [self as "SBDebugger*", executable as "const char*", add_dependent_modules as "bool"]
+            -> Result<SBTarget, SBError> as "SBTarget" {
+            SBError error;
+            SBTarget target = self->CreateTarget(executable, nullptr, nullptr, add_dependent_modules, error);
+            return error.Success() ? Ok(target) : Err(error);
+        })
+    }
+}
+
+// Runs `init_commands` through the debugger's command interpreter and registers the bundled
+// Rust `type summary add` / `type synthetic add` providers (for `&str`, `String`, `Option`,
+// `Vec` and `HashMap`) so locals show up as readable values instead of raw addresses, the same
+// way `rust-lldb`'s wrapper script does. Enums other than `Option` get no provider.
+pub fn initialize_rust_formatters(debugger: &SBDebugger, init_commands: &[String]) {
+    let interpreter = debugger.command_interpreter();
+    let mut result = SBCommandReturnObject::new();
+    for command in init_commands {
+        interpreter.handle_command(command, &mut result, false);
+    }
+
+    let category = debugger.type_category("Rust");
+    category.set_enabled(true);
+    for (type_regex, summary_fn) in RUST_TYPE_SUMMARIES {
+        category.add_type_summary(type_regex, summary_fn);
+    }
+    for (type_regex, synth_class) in RUST_TYPE_SYNTHETICS {
+        category.add_type_synthetic(type_regex, synth_class);
+    }
+}
+
+// (regex over the LLDB-mangled type name, Python summary function name)
+const RUST_TYPE_SUMMARIES: &[(&str, &str)] = &[
+    (r"^&str$", "rust_formatters.str_summary"),
+    (r"^(alloc::string::String|String)$", "rust_formatters.string_summary"),
+    (r"^core::option::Option<.+>$", "rust_formatters.option_summary"),
+];
+
+// (regex over the LLDB-mangled type name, Python synthetic child provider class name)
+const RUST_TYPE_SYNTHETICS: &[(&str, &str)] = &[
+    (r"^(alloc::vec::Vec|Vec)<.+>$", "rust_formatters.VecSynthProvider"),
+    (r"^(std::collections::HashMap|HashMap)<.+>$", "rust_formatters.HashMapSynthProvider"),
+];
+
+cpp_class!(pub unsafe struct SBCommandInterpreter as "SBCommandInterpreter");
+impl SBCommandInterpreter {
+    pub fn handle_command(&self, command: &str, result: &mut SBCommandReturnObject, add_to_history: bool) {
+        let command = command.as_ptr() as *const c_char;
+        cpp!(unsafe [self as "SBCommandInterpreter*", command as "const char*",
+                     result as "SBCommandReturnObject*", add_to_history as "bool"] {
+            self->HandleCommand(command, *result, add_to_history);
+        })
+    }
+    pub fn handle_command_with_context(
+        &self, command: &str, context: &SBExecutionContext, result: &mut SBCommandReturnObject, add_to_history: bool,
+    ) {
+        let command = command.as_ptr() as *const c_char;
+        cpp!(unsafe [self as "SBCommandInterpreter*", command as "const char*", context as "SBExecutionContext*",
+                     result as "SBCommandReturnObject*", add_to_history as "bool"] {
+            self->HandleCommand(command, *context, *result, add_to_history);
+        })
+    }
+    // LLDB 11 replaced the 6-argument `HandleCompletion` overload (which took separate
+    // match/description string lists to fill in by output parameter) with one that fills in only
+    // a match list; gate on the detected API version so older LLDBs still link.
+    #[cfg(lldb_api = "11")]
+    pub fn handle_completion(&self, command: &str, cursor_pos: u32) -> Vec<String> {
+        let command = command.as_ptr() as *const c_char;
+        cpp!(unsafe [self as "SBCommandInterpreter*", command as "const char*", cursor_pos as "uint32_t"]
+            -> Vec<String> as "StringVec" {
+            SBStringList matches;
+            self->HandleCompletion(command, cursor_pos, 0, -1, matches);
+            return matches;
+        })
+    }
+    #[cfg(not(lldb_api = "11"))]
+    pub fn handle_completion(&self, command: &str, cursor_pos: u32) -> Vec<String> {
+        let command = command.as_ptr() as *const c_char;
+        cpp!(unsafe [self as "SBCommandInterpreter*", command as "const char*", cursor_pos as "uint32_t"]
+            -> Vec<String> as "StringVec" {
+            SBStringList matches, descriptions;
+            self->HandleCompletion(command, cursor_pos, 0, -1, matches, descriptions);
+            return matches;
+        })
+    }
+}
+
+cpp_class!(pub unsafe struct SBCommandReturnObject as "SBCommandReturnObject");
+impl SBCommandReturnObject {
+    pub fn new() -> SBCommandReturnObject {
+        cpp!(unsafe [] -> SBCommandReturnObject as "SBCommandReturnObject" { return SBCommandReturnObject(); })
+    }
+    pub fn succeeded(&self) -> bool {
+        cpp!(unsafe [self as "SBCommandReturnObject*"] -> bool as "bool" { return self->Succeeded(); })
+    }
+    pub fn output(&self) -> &CStr {
+        let ptr = cpp!(unsafe [self as "SBCommandReturnObject*"] -> *const c_char as "const char*" { return self->GetOutput(); });
+        unsafe { CStr::from_ptr(ptr) }
+    }
+    pub fn error(&self) -> &CStr {
+        let ptr = cpp!(unsafe [self as "SBCommandReturnObject*"] -> *const c_char as "const char*" { return self->GetError(); });
+        unsafe { CStr::from_ptr(ptr) }
+    }
+}
+
+cpp_class!(pub unsafe struct SBExecutionContext as "SBExecutionContext");
+impl SBExecutionContext {
+    pub fn from_frame(frame: &SBFrame) -> SBExecutionContext {
+        cpp!(unsafe [frame as "SBFrame*"] -> SBExecutionContext as "SBExecutionContext" {
+            return SBExecutionContext(*frame);
+        })
+    }
+    pub fn from_thread(thread: &SBThread) -> SBExecutionContext {
+        cpp!(unsafe [thread as "SBThread*"] -> SBExecutionContext as "SBExecutionContext" {
+            return SBExecutionContext(*thread);
+        })
+    }
+    pub fn from_target(target: &SBTarget) -> SBExecutionContext {
+        cpp!(unsafe [target as "SBTarget*"] -> SBExecutionContext as "SBExecutionContext" {
+            return SBExecutionContext(*target);
+        })
+    }
+}
+
+// A named collection of type formatters ("categories" in LLDB parlance). The adapter registers
+// its bundled Rust formatters under a dedicated "Rust" category so they can be toggled as a unit.
+cpp_class!(pub unsafe struct SBTypeCategory as "SBTypeCategory");
+impl SBTypeCategory {
+    pub fn set_enabled(&self, enabled: bool) {
+        cpp!(unsafe [self as "SBTypeCategory*", enabled as "bool"] { self->SetEnabled(enabled); })
+    }
+    pub fn add_type_summary(&self, type_name_regex: &str, python_function_name: &str) {
+        let type_name_regex = type_name_regex.as_ptr() as *const c_char;
+        let python_function_name = python_function_name.as_ptr() as *const c_char;
+        cpp!(unsafe [self as "SBTypeCategory*", type_name_regex as "const char*", python_function_name as "const char*"] {
+            SBTypeNameSpecifier spec(type_name_regex, true /* is_regex */);
+            SBTypeSummary summary = SBTypeSummary::CreateWithFunctionName(python_function_name);
+            self->AddTypeSummary(spec, summary);
+        })
+    }
+    pub fn add_type_synthetic(&self, type_name_regex: &str, python_class_name: &str) {
+        let type_name_regex = type_name_regex.as_ptr() as *const c_char;
+        let python_class_name = python_class_name.as_ptr() as *const c_char;
+        cpp!(unsafe [self as "SBTypeCategory*", type_name_regex as "const char*", python_class_name as "const char*"] {
+            SBTypeNameSpecifier spec(type_name_regex, true /* is_regex */);
+            SBTypeSynthetic synth = SBTypeSynthetic::CreateWithClassName(python_class_name);
+            self->AddTypeSynthetic(spec, synth);
+        })
+    }
+}
+
+impl SBDebugger {
+    pub fn type_category(&self, name: &str) -> SBTypeCategory {
+        let name = name.as_ptr() as *const c_char;
+        cpp!(unsafe [self as "SBDebugger*", name as "const char*"] -> SBTypeCategory as "SBTypeCategory" {
+            return self->GetCategory(name);
+        })
+    }
+}
+
+cpp_class!(pub unsafe struct SBError as "SBError");
+impl SBError {
+    pub fn is_success(&self) -> bool {
+        cpp!(unsafe [self as "SBError*"] -> bool as "bool" { return self->Success(); })
+    }
+    pub fn message(&self) -> &CStr {
+        let ptr = cpp!(unsafe [self as "SBError*"] -> *const c_char as "const char*" { return self->GetCString(); });
+        unsafe { CStr::from_ptr(ptr) }
+    }
+}
+
+cpp_class!(pub unsafe struct SBTarget as "SBTarget");
+impl SBTarget {
+    pub fn launch(&self, launch_info: &SBLaunchInfo) -> Result<SBProcess, SBError> {
+        cpp!(unsafe [self as "SBTarget*", launch_info as "SBLaunchInfo*"] -> Result<SBProcess, SBError> as "SBProcess" {
+            SBError error;
+            SBProcess process = self->Launch(*launch_info, error);
+            return error.Success() ? Ok(process) : Err(error);
+        })
+    }
+}
+
+cpp_class!(pub unsafe struct SBProcess as "SBProcess");
+
+cpp_class!(pub unsafe struct SBLaunchInfo as "SBLaunchInfo");
+impl SBLaunchInfo {
+    pub fn new() -> SBLaunchInfo {
+        cpp!(unsafe [] -> SBLaunchInfo as "SBLaunchInfo" { return SBLaunchInfo(nullptr); })
+    }
+    pub fn set_launch_flags(&mut self, flags: u32) {
+        cpp!(unsafe [self as "SBLaunchInfo*", flags as "uint32_t"] { self->SetLaunchFlags(flags); })
+    }
+}
@@ -0,0 +1,60 @@
+//! A small synchronous convenience layer over the raw `cpp!` bindings in `lldb.rs`, modeled on
+//! the ergonomics of the standalone `lldb` crate. This is not used by the adapter's event-driven
+//! `DebugSession` (which drives LLDB asynchronously off `SBListener` events) - it exists as a
+//! minimal, RAII-wrapped seam for integration tests and other code that just wants to launch a
+//! program and block until it stops, without hand-writing `cpp!` blocks.
+
+use crate::lldb::{LaunchFlag, SBDebugger, SBError, SBLaunchInfo, SBProcess, SBTarget};
+
+pub fn initialize() {
+    SBDebugger::initialize();
+}
+
+pub fn terminate() {
+    SBDebugger::terminate();
+}
+
+pub struct Debugger(SBDebugger);
+
+impl Debugger {
+    pub fn create(source_init_files: bool) -> Debugger {
+        let debugger = SBDebugger::create(source_init_files);
+        debugger.set_async(false);
+        Debugger(debugger)
+    }
+
+    pub fn create_target_simple(&self, executable: &str) -> Result<Target, SBError> {
+        self.0
+            .create_target(executable, None, None, false)
+            .map(Target)
+    }
+}
+
+pub struct Target(SBTarget);
+
+impl Target {
+    pub fn launch(&self, launch_info: LaunchInfo) -> Result<SBProcess, SBError> {
+        self.0.launch(&launch_info.0)
+    }
+}
+
+pub struct LaunchInfo(SBLaunchInfo);
+
+impl LaunchInfo {
+    pub fn new() -> LaunchInfo {
+        LaunchInfo(SBLaunchInfo::new())
+    }
+
+    pub fn stop_at_entry(mut self, stop: bool) -> LaunchInfo {
+        if stop {
+            self.0.set_launch_flags(LaunchFlag::StopAtEntry as u32);
+        }
+        self
+    }
+}
+
+impl Default for LaunchInfo {
+    fn default() -> LaunchInfo {
+        LaunchInfo::new()
+    }
+}
@@ -1,3 +1,4 @@
+use base64;
 use globset;
 use regex;
 use serde_json;
@@ -5,7 +6,7 @@ use serde_json;
 use std;
 use std::borrow::Cow;
 use std::boxed::FnBox;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::collections::hash_map::Entry;
 use std::collections::{HashMap, HashSet};
 use std::env;
@@ -15,6 +16,7 @@ use std::option;
 use std::path::{self, Component, Path, PathBuf};
 use std::rc::Rc;
 use std::str;
+use std::time::{Duration, Instant};
 
 use futures::sync::mpsc;
 
@@ -26,6 +28,7 @@ use crate::expressions;
 use crate::handles::{self, Handle, HandleTree};
 use crate::must_initialize::{Initialized, MustInitialize, NotInitialized};
 use crate::python::{self, PythonValue};
+use crate::rhai_support;
 use crate::source_map;
 use crate::terminal::Terminal;
 use lldb::*;
@@ -53,6 +56,18 @@ enum BreakpointKind {
         adapter_data: Vec<u8>,
     },
     Exception,
+    Watchpoint {
+        address: u64,
+        size: u32,
+        kind: WatchpointKind,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WatchpointKind {
+    Read,
+    Write,
+    ReadWrite,
 }
 
 #[derive(Debug, Clone)]
@@ -62,6 +77,56 @@ struct BreakpointInfo {
     condition: Option<String>,
     log_message: Option<String>,
     ignore_count: u32,
+    hit_condition: Option<String>,
+}
+
+// A compiled breakpoint condition that needs to run inside our own callback rather than as a
+// native LLDB condition string (see `init_bp_actions`).
+#[derive(Debug, Clone)]
+enum BpCondition {
+    Python(String),
+    Rhai(String),
+}
+
+// A DAP `hitCondition` string, parsed. ">N"/bare "N" reduce to LLDB's native ignore count;
+// "==N"/"%N" have no native LLDB equivalent and need a counting callback instead.
+#[derive(Debug, Clone, Copy)]
+enum HitCondition {
+    GreaterThan(u32),
+    Equal(u32),
+    Modulo(u32),
+}
+
+fn parse_hit_condition(expr: &str) -> Option<HitCondition> {
+    let expr = expr.trim();
+    if let Some(rest) = expr.strip_prefix("==") {
+        rest.trim().parse().ok().map(HitCondition::Equal)
+    } else if let Some(rest) = expr.strip_prefix('%') {
+        rest.trim().parse().ok().map(HitCondition::Modulo)
+    } else if let Some(rest) = expr.strip_prefix('>') {
+        rest.trim().parse().ok().map(HitCondition::GreaterThan)
+    } else {
+        expr.parse().ok().map(HitCondition::GreaterThan)
+    }
+}
+
+// A reverse request we're waiting on a correlated response for.
+enum PendingRequest {
+    // We asked the client to spawn the debuggee in its integrated/external terminal; once it
+    // replies with the spawned process's pid, finish the launch by attaching LLDB to it and
+    // deliver the (until now parked) response to the original `launch` request.
+    RunInTerminal {
+        launch_request_seq: u32,
+        args: LaunchRequestArguments,
+    },
+}
+
+// The original `launch`/`attach` arguments, kept around so `handle_restart` can relaunch (or
+// reattach) the same target without the client having to resend them.
+#[derive(Debug, Clone)]
+enum RestartSource {
+    Launch(LaunchRequestArguments),
+    Attach(AttachRequestArguments),
 }
 
 enum Container {
@@ -71,12 +136,39 @@ enum Container {
     Globals(SBFrame),
     Registers(SBFrame),
     Container(SBValue),
+    // A synthetic view over a `[start..end)` sub-range of `base`'s children, created by
+    // `evaluate_slice` for an `expr[a:b]` evaluate request so the slice can be expanded in the
+    // Variables pane the same way any other container can.
+    Range(SBValue, u32, u32),
+    // A synthetic row-major N-D grid view over `base`'s flat children, created by
+    // `evaluate_reshape` for an `expr,[rows,cols]` evaluate request. `dims` is the shape still to
+    // be expanded at and below this node, and `offset` is this node's starting flat index into
+    // `base`; `handle_variables` peels one dimension off per level until only the innermost
+    // element range is left.
+    Grid(SBValue, Rc<Vec<usize>>, usize),
+}
+
+// A user-registered override for how a typed `SBValue` renders when no plain value/summary is
+// available (see `get_var_value_str`), keyed by a regex over the value's type name. Either
+// `template` (a string interpolating `{$.field}` child lookups) or `script` (a Rhai expression
+// run via `rhai_support::evaluate_value`, with `$` bound to the value) takes precedence over the
+// default `{name:value, ...}` rendering; `max_length`/`max_children` replace the fixed constants
+// that rendering otherwise uses.
+struct SummaryProvider {
+    type_regex: regex::Regex,
+    template: Option<String>,
+    script: Option<String>,
+    max_length: usize,
+    max_children: usize,
 }
 
 enum ExprType {
     Native,
     Python,
     Simple,
+    // Sandboxed Rhai scripts (`/rhai `) - an alternative to `Python` for conditional
+    // breakpoints/watch expressions on builds with no embedded Python interpreter.
+    Rhai,
 }
 
 pub struct DebugSession {
@@ -89,10 +181,45 @@ pub struct DebugSession {
     process: MustInitialize<SBProcess>,
     process_launched: bool,
     on_configuration_done: Option<(u32, Box<AsyncResponder>)>,
+    // Reverse requests (adapter -> client) that are awaiting a correlated response, keyed by the
+    // seq we sent them under. `handle_response` dispatches into these when the reply arrives.
+    pending_requests: HashMap<u32, PendingRequest>,
+    // Set by `complete_launch` when it parks the launch response on a `runInTerminal` round-trip
+    // instead of answering immediately; checked by `handle_configuration_done` so it doesn't send
+    // a second, premature response.
+    deferred_response_pending: bool,
     source_breakpoints: HashMap<FileId, HashMap<i64, BreakpointID>>,
     fn_breakpoints: HashMap<String, BreakpointID>,
+    // Keyed by the DAP exception filter id ("cpp_throw", "rust_panic", ...).
+    exception_breakpoints: HashMap<String, BreakpointID>,
+    // Keyed by the `dataId` we handed out from `dataBreakpointInfo` ("<load address in hex>/<size>").
+    data_breakpoints: HashMap<String, BreakpointID>,
     breakpoints: RefCell<HashMap<BreakpointID, BreakpointInfo>>,
     var_refs: HandleTree<Container>,
+    summary_providers: Vec<SummaryProvider>,
+    // Targets handed out by the last `stepInTargets` request, keyed by the id we assigned;
+    // consulted by `handle_step_in` when the client's `stepIn` request carries a `target_id`.
+    step_in_targets: HashMap<i64, String>,
+    // How long `handle_pause` gives the inferior to actually reach `Stopped` before warning that
+    // it looks wedged; configurable via `launch`/`attach`'s `interrupt_timeout` (seconds).
+    interrupt_timeout: Duration,
+    // Set when `handle_pause` asks LLDB to stop the process; cleared by `handle_process_event`
+    // once a `Stopped`/`Crashed` event actually arrives. Checked by `check_interrupt_timeout`,
+    // which the session's event loop calls on every pass so a wedged inferior doesn't go unnoticed.
+    interrupt_requested_at: Option<Instant>,
+    // Per-thread `StackFrame`s resolved so far during the current stop (see `handle_stack_trace`).
+    // Cleared in `before_resume`, since frame handles only stay valid for one stop.
+    stack_frame_cache: HashMap<ThreadID, Vec<StackFrame>>,
+    restart_source: Option<RestartSource>,
+    // Set by `handle_restart` while it's waiting for the killed process's `Exited`/`Detached`
+    // event; `handle_process_event` checks it to relaunch in place instead of tearing the
+    // session down as it would for a normal process exit.
+    pending_restart: bool,
+    // Set by `relaunch` once it has sent the post-restart `initialized` event, so the actual
+    // re-launch/re-attach waits for the client's next `configurationDone` the same way the
+    // original `launch`/`attach` does - otherwise a breakpoint set in reaction to `initialized`
+    // could miss the process's first run after a restart.
+    pending_relaunch: Option<RestartSource>,
     disassembly: MustInitialize<disassembly::AddressSpace>,
     known_threads: HashSet<ThreadID>,
     source_map: source_map::SourceMap,
@@ -130,10 +257,22 @@ impl DebugSession {
             process_launched: false,
             event_listener: SBListener::new_with_name("DebugSession"),
             on_configuration_done: None,
+            pending_requests: HashMap::new(),
+            deferred_response_pending: false,
             source_breakpoints: HashMap::new(),
             fn_breakpoints: HashMap::new(),
+            exception_breakpoints: HashMap::new(),
+            data_breakpoints: HashMap::new(),
             breakpoints: RefCell::new(HashMap::new()),
             var_refs: HandleTree::new(),
+            summary_providers: Vec::new(),
+            step_in_targets: HashMap::new(),
+            interrupt_timeout: Self::default_interrupt_timeout(),
+            interrupt_requested_at: None,
+            stack_frame_cache: HashMap::new(),
+            restart_source: None,
+            pending_restart: false,
+            pending_relaunch: None,
             disassembly: NotInitialized,
             known_threads: HashSet::new(),
             source_map: source_map::SourceMap::empty(),
@@ -151,6 +290,10 @@ impl DebugSession {
     }
 
     fn handle_message(&mut self, message: ProtocolMessage) {
+        // Runs on every inbound message, not just LLDB debug events, so a pending `pause` that
+        // never lands still gets noticed once the client sends anything else (another request,
+        // or a reverse-request response) rather than only on the next unrelated debug event.
+        self.check_interrupt_timeout();
         match message {
             ProtocolMessage::Request(request) => self.handle_request(request),
             ProtocolMessage::Response(response) => self.handle_response(response),
@@ -158,7 +301,49 @@ impl DebugSession {
         };
     }
 
-    fn handle_response(&mut self, response: Response) {}
+    fn handle_response(&mut self, response: Response) {
+        let pending = match self.pending_requests.remove(&response.request_seq) {
+            Some(pending) => pending,
+            None => {
+                error!("Received response to a request we aren't waiting on: {:?}", response);
+                return;
+            }
+        };
+        match pending {
+            PendingRequest::RunInTerminal { launch_request_seq, args } => {
+                let result = match response.body {
+                    Some(ResponseBody::runInTerminal(body)) => self.complete_launch_in_terminal(args, body),
+                    _ => Err(Error::Internal("Client did not return a runInTerminal response".into())),
+                };
+                self.send_response(launch_request_seq, result);
+            }
+        }
+    }
+
+    // Finishes a `launch` whose debuggee was spawned by the client's integrated/external
+    // terminal: attach LLDB to the pid the client handed back instead of launching it ourselves.
+    fn complete_launch_in_terminal(
+        &mut self, args: LaunchRequestArguments, response: RunInTerminalResponseBody,
+    ) -> Result<ResponseBody, Error> {
+        // `externalTerminal` clients commonly only populate `shellProcessId` (the wrapping
+        // shell's pid, not the debuggee's yet), so fall back to that rather than erroring out.
+        let pid = response.process_id.or(response.shell_process_id).ok_or_else(|| {
+            Error::UserError("Client did not report the pid of the process it launched".into())
+        })?;
+
+        let error = self.target.attach_to_process_with_id(&self.event_listener, pid as u64);
+        if !error.is_success() {
+            return Err(error.into());
+        }
+        self.process = Initialized(self.target.process());
+        self.process_launched = true;
+
+        if let Some(ref commands) = args.post_run_commands {
+            self.exec_commands(commands);
+        }
+        self.exit_commands = args.exit_commands;
+        Ok(ResponseBody::launch)
+    }
 
     fn handle_request(&mut self, request: Request) {
         let result = if let Some(arguments) = request.arguments {
@@ -176,8 +361,17 @@ impl DebugSession {
                 RequestArguments::setExceptionBreakpoints(args) =>
                     self.handle_set_exception_breakpoints(args)
                         .map(|r| ResponseBody::setExceptionBreakpoints),
+                RequestArguments::exceptionInfo(args) =>
+                    self.handle_exception_info(args)
+                        .map(|r| ResponseBody::exceptionInfo(r)),
+                RequestArguments::dataBreakpointInfo(args) =>
+                    self.handle_data_breakpoint_info(args)
+                        .map(|r| ResponseBody::dataBreakpointInfo(r)),
+                RequestArguments::setDataBreakpoints(args) =>
+                    self.handle_set_data_breakpoints(args)
+                        .map(|r| ResponseBody::setDataBreakpoints(r)),
                 RequestArguments::launch(args) => {
-                    match self.handle_launch(args) {
+                    match self.handle_launch(args, request.seq) {
                         Ok(responder) => {
                             self.on_configuration_done = Some((request.seq, responder));
                             return; // launch responds asynchronously
@@ -227,15 +421,33 @@ impl DebugSession {
                 RequestArguments::stepOut(args) =>
                     self.handle_step_out(args)
                         .map(|r| ResponseBody::stepOut),
+                RequestArguments::stepInTargets(args) =>
+                    self.handle_step_in_targets(args)
+                        .map(|r| ResponseBody::stepInTargets(r)),
                 RequestArguments::source(args) =>
                     self.handle_source(args)
                         .map(|r| ResponseBody::source(r)),
                 RequestArguments::disconnect(args) =>
                     self.handle_disconnect(Some(args))
                         .map(|_| ResponseBody::disconnect),
+                RequestArguments::restart(args) =>
+                    self.handle_restart(args)
+                        .map(|_| ResponseBody::restart),
                 RequestArguments::displaySettings(args) =>
                     self.handle_display_settings(args)
                         .map(|_| ResponseBody::displaySettings),
+                RequestArguments::completions(args) =>
+                    self.handle_completions(args)
+                        .map(|r| ResponseBody::completions(r)),
+                RequestArguments::readMemory(args) =>
+                    self.handle_read_memory(args)
+                        .map(|r| ResponseBody::readMemory(r)),
+                RequestArguments::writeMemory(args) =>
+                    self.handle_write_memory(args)
+                        .map(|r| ResponseBody::writeMemory(r)),
+                RequestArguments::findInVariables(args) =>
+                    self.handle_find_in_variables(args)
+                        .map(|r| ResponseBody::findInVariables(r)),
                 _ => {
                     //error!("No handler for request message: {:?}", request);
                     Err(Error::Internal("Not implemented.".into()))
@@ -311,7 +523,7 @@ impl DebugSession {
         self.debugger = Initialized(SBDebugger::create(false));
         self.debugger.set_async(true);
         python::initialize(&self.debugger.command_interpreter());
-        let mut command_result = SBCommandReturnObject::new();
+        initialize_rust_formatters(&self.debugger, &[]);
 
         let caps = Capabilities {
             supports_configuration_done_request: true,
@@ -320,10 +532,17 @@ impl DebugSession {
             supports_conditional_breakpoints: true,
             supports_hit_conditional_breakpoints: true,
             supports_set_variable: true,
-            supports_completions_request: false, // TODO
+            supports_completions_request: true,
             supports_delayed_stack_trace_loading: true,
             support_terminate_debuggee: true,
             supports_log_points: true,
+            supports_exception_info_request: true,
+            supports_data_breakpoints: true,
+            supports_memory_references: true,
+            supports_read_memory_request: true,
+            supports_write_memory_request: true,
+            supports_step_in_targets_request: true,
+            supports_restart_request: true,
         };
         Ok(caps)
     }
@@ -384,6 +603,7 @@ impl DebugSession {
                     condition: None,
                     log_message: None,
                     ignore_count: 0,
+                    hit_condition: None,
                 };
 
                 let bp_id = bp_info.id;
@@ -417,6 +637,7 @@ impl DebugSession {
             };
             bp_info.condition = req.condition.clone();
             bp_info.log_message = req.log_message.clone();
+            bp_info.hit_condition = req.hit_condition.clone();
 
             self.init_bp_actions(&mut bp, bp_info);
 
@@ -451,12 +672,14 @@ impl DebugSession {
                         condition: None,
                         log_message: None,
                         ignore_count: 0,
+                        hit_condition: None,
                     };
                     let bp_info = breakpoints.entry(bp_info.id).or_insert(bp_info);
                     (bp, bp_info)
                 }
             };
             bp_info.condition = bp_req.condition;
+            bp_info.hit_condition = bp_req.hit_condition;
 
             let bp_id = bp_info.id;
             self.init_bp_actions(&mut bp, bp_info);
@@ -484,9 +707,333 @@ impl DebugSession {
     }
 
     fn handle_set_exception_breakpoints(&mut self, args: SetExceptionBreakpointsArguments) -> Result<(), Error> {
+        let requested: HashSet<&str> = args.filters.iter().map(|f| f.as_str()).collect();
+
+        let mut breakpoints = self.breakpoints.borrow_mut();
+        let mut new_exception_breakpoints = HashMap::new();
+        for filter in &args.filters {
+            let bp = match self.exception_breakpoints.get(filter) {
+                Some(bp_id) => self.target.find_breakpoint_by_id(*bp_id),
+                None => match self.create_exception_breakpoint(filter) {
+                    Some(bp) => bp,
+                    None => {
+                        error!("Don't know how to set an exception breakpoint for filter {:?}", filter);
+                        continue;
+                    }
+                },
+            };
+            let bp_id = bp.id();
+            breakpoints.entry(bp_id).or_insert_with(|| BreakpointInfo {
+                id: bp_id,
+                kind: BreakpointKind::Exception,
+                condition: None,
+                log_message: None,
+                ignore_count: 0,
+                hit_condition: None,
+            });
+            new_exception_breakpoints.insert(filter.clone(), bp_id);
+        }
+
+        // Clear breakpoints for filters the client dropped.
+        for (filter, bp_id) in &self.exception_breakpoints {
+            if !requested.contains(filter.as_str()) {
+                self.target.breakpoint_delete(*bp_id);
+                breakpoints.remove(bp_id);
+            }
+        }
+        drop(breakpoints);
+        self.exception_breakpoints = new_exception_breakpoints;
         Ok(())
     }
 
+    // Maps a DAP exception filter id to the LLDB breakpoint that implements it. C++ and Swift
+    // get real exception breakpoints via the language-specific SB API; Rust has no such API, so
+    // a panic is instead caught by breaking on the `rust_panic` symbol that `std` always calls
+    // into on unwind.
+    fn create_exception_breakpoint(&self, filter: &str) -> Option<SBBreakpoint> {
+        match filter {
+            "cpp_throw" => Some(
+                self.target
+                    .breakpoint_create_for_exception(LanguageType::CPlusPlus, false, true),
+            ),
+            "cpp_catch" => Some(
+                self.target
+                    .breakpoint_create_for_exception(LanguageType::CPlusPlus, true, false),
+            ),
+            "swift_throw" => Some(self.target.breakpoint_create_for_exception(LanguageType::Swift, false, true)),
+            "swift_catch" => Some(self.target.breakpoint_create_for_exception(LanguageType::Swift, true, false)),
+            "rust_panic" => Some(self.target.breakpoint_create_by_name("rust_panic")),
+            _ => None,
+        }
+    }
+
+    fn handle_exception_info(&mut self, args: ExceptionInfoArguments) -> Result<ExceptionInfoResponseBody, Error> {
+        let thread = self.process.thread_by_id(args.thread_id as ThreadID)?;
+        let frame = thread.frame_at_index(0);
+
+        let description = thread.stop_description();
+        let exception_id = match thread.stop_reason() {
+            StopReason::Signal => format!("signal {}", thread.stop_reason_data_at_index(0)),
+            StopReason::Exception => self
+                .exception_type_name(&frame)
+                .unwrap_or_else(|| "exception".to_owned()),
+            _ => "unknown".to_owned(),
+        };
+
+        // C++/ObjC/Swift exceptions are thrown objects sitting in a well-known frame variable;
+        // evaluate it so the exception panel can show a type name and message beyond the raw
+        // stop description. This is best-effort: not every language/stop reason has one.
+        let details = self.evaluate_expr_in_frame("$lldb_exception", Some(&frame)).ok().map(|val| {
+            let message = match val {
+                PythonValue::SBValue(ref sbval) => sbval.summary().map(into_string_lossy),
+                _ => None,
+            };
+            // We're still stopped at the throw site, so the thread's current backtrace *is* the
+            // throw-site stack trace the DAP client wants to show alongside the exception panel.
+            let stack_trace = self.render_exception_stack_trace(&thread);
+            ExceptionDetails {
+                message,
+                type_name: self.exception_type_name(&frame),
+                full_type_name: None,
+                stack_trace,
+            }
+        });
+
+        Ok(ExceptionInfoResponseBody {
+            exception_id,
+            description: Some(description),
+            break_mode: ExceptionBreakMode::Always,
+            details,
+        })
+    }
+
+    // Renders `thread`'s current frames as a plain-text backtrace for `ExceptionDetails::stack_trace`.
+    fn render_exception_stack_trace(&self, thread: &SBThread) -> Option<String> {
+        let mut lines = vec![];
+        for i in 0..thread.num_frames() {
+            let frame = thread.frame_at_index(i);
+            if !frame.is_valid() {
+                break;
+            }
+            let name = frame.function_name().unwrap_or("<unknown>");
+            match frame.line_entry() {
+                Some(le) => lines.push(format!("  at {} ({}:{})", name, le.file_spec().filename(), le.line())),
+                None => lines.push(format!("  at {}", name)),
+            }
+        }
+        if lines.is_empty() {
+            None
+        } else {
+            Some(lines.join("\n"))
+        }
+    }
+
+    fn exception_type_name(&self, frame: &SBFrame) -> Option<String> {
+        match self.evaluate_expr_in_frame("$lldb_exception", Some(frame)) {
+            Ok(PythonValue::SBValue(sbval)) => sbval.type_name().map(|s| s.to_owned()),
+            _ => None,
+        }
+    }
+
+    fn handle_data_breakpoint_info(
+        &mut self, args: DataBreakpointInfoArguments,
+    ) -> Result<DataBreakpointInfoResponseBody, Error> {
+        let var = match args.variables_reference.and_then(handles::from_i64).and_then(|h| self.var_refs.get(h)) {
+            Some(Container::Container(var)) => var.child_member_with_name(&args.name),
+            Some(Container::Locals(frame)) | Some(Container::Statics(frame)) | Some(Container::Globals(frame)) => {
+                frame.find_variable(&args.name)
+            }
+            _ => None,
+        };
+
+        let var = match var {
+            Some(var) => var,
+            None => {
+                return Ok(DataBreakpointInfoResponseBody {
+                    data_id: None,
+                    description: format!("No such variable: {}", args.name),
+                    access_types: None,
+                })
+            }
+        };
+
+        let address = var.load_address();
+        if address == INVALID_ADDRESS {
+            return Ok(DataBreakpointInfoResponseBody {
+                data_id: None,
+                description: "This value is not resident in memory (it may live in a register)".to_owned(),
+                access_types: None,
+            });
+        }
+
+        let size = var.byte_size();
+        Ok(DataBreakpointInfoResponseBody {
+            data_id: Some(format!("{:x}/{}", address, size)),
+            description: args.name,
+            access_types: Some(vec![
+                DataBreakpointAccessType::Read,
+                DataBreakpointAccessType::Write,
+                DataBreakpointAccessType::ReadWrite,
+            ]),
+        })
+    }
+
+    fn handle_set_data_breakpoints(
+        &mut self, args: SetDataBreakpointsArguments,
+    ) -> Result<SetDataBreakpointsResponseBody, Error> {
+        let mut breakpoints_resp = vec![];
+        let mut new_data_breakpoints = HashMap::new();
+        let mut breakpoints = self.breakpoints.borrow_mut();
+
+        for req in &args.breakpoints {
+            let (address, size) = match parse_data_id(&req.data_id) {
+                Some(v) => v,
+                None => {
+                    breakpoints_resp.push(Breakpoint {
+                        verified: false,
+                        message: Some("Invalid dataId".to_owned()),
+                        ..Default::default()
+                    });
+                    continue;
+                }
+            };
+            let kind = match req.access_type {
+                Some(DataBreakpointAccessType::Read) => WatchpointKind::Read,
+                Some(DataBreakpointAccessType::ReadWrite) => WatchpointKind::ReadWrite,
+                Some(DataBreakpointAccessType::Write) | None => WatchpointKind::Write,
+            };
+
+            let mut wp = match self.data_breakpoints.get(&req.data_id) {
+                Some(wp_id) => self.target.find_watchpoint_by_id(*wp_id),
+                None => {
+                    let (read, write) = match kind {
+                        WatchpointKind::Read => (true, false),
+                        WatchpointKind::Write => (false, true),
+                        WatchpointKind::ReadWrite => (true, true),
+                    };
+                    self.target.watch_address(address, size as usize, read, write)
+                }
+            };
+
+            let bp_id = wp.id();
+            let bp_info = breakpoints.entry(bp_id).or_insert_with(|| BreakpointInfo {
+                id: bp_id,
+                kind: BreakpointKind::Watchpoint { address, size, kind },
+                condition: None,
+                log_message: None,
+                ignore_count: 0,
+                hit_condition: None,
+            });
+            bp_info.condition = req.condition.clone();
+            bp_info.hit_condition = req.hit_condition.clone();
+            self.init_wp_actions(&mut wp, bp_info);
+
+            new_data_breakpoints.insert(req.data_id.clone(), bp_id);
+            breakpoints_resp.push(Breakpoint { verified: true, ..Default::default() });
+        }
+
+        // Delete watchpoints for dataIds the client dropped, same reconciliation as source bps.
+        for (data_id, wp_id) in &self.data_breakpoints {
+            if !new_data_breakpoints.contains_key(data_id) {
+                self.target.watchpoint_delete(*wp_id);
+                breakpoints.remove(wp_id);
+            }
+        }
+        drop(breakpoints);
+        self.data_breakpoints = new_data_breakpoints;
+
+        Ok(SetDataBreakpointsResponseBody { breakpoints: breakpoints_resp })
+    }
+
+    // Condition and hit-count handling for watchpoints, mirroring `init_bp_actions` (log messages
+    // aren't meaningful for watchpoints since there's no source line to report them against).
+    fn init_wp_actions(&self, wp: &mut SBWatchpoint, bp_info: &BreakpointInfo) {
+        fn evaluate_python_wp_condition(expr: &str, process: &SBProcess) -> bool {
+            let debugger = process.target().debugger();
+            let interpreter = debugger.command_interpreter();
+            let context = SBExecutionContext::from_target(&process.target());
+            match python::evaluate(&interpreter, &expr, true, &context) {
+                Err(_) => true,
+                Ok(val) => match val {
+                    PythonValue::SBValue(val) => match val.try_value_as_unsigned() {
+                        Ok(val) => val != 0,
+                        Err(_) => true,
+                    },
+                    PythonValue::Bool(val) => val,
+                    _ => true,
+                },
+            }
+        }
+
+        fn evaluate_rhai_wp_condition(expr: &str, process: &SBProcess) -> bool {
+            let frame = process.selected_thread().frame_at_index(0);
+            match rhai_support::evaluate(&frame, expr) {
+                Ok(PythonValue::SBValue(val)) => val.try_value_as_unsigned().map(|v| v != 0).unwrap_or(true),
+                Ok(PythonValue::Bool(val)) => val,
+                Ok(PythonValue::Int(val)) => val != 0,
+                _ => true,
+            }
+        }
+
+        let mut native_condition = None;
+        let mut callback_condition = None;
+        if let Some(ref condition) = bp_info.condition {
+            let (expr, ty) = self.get_expression_type(condition);
+            match ty {
+                ExprType::Native => native_condition = Some(expr.to_owned()),
+                ExprType::Simple => callback_condition = Some(BpCondition::Python(expressions::preprocess_simple_expr(expr))),
+                ExprType::Python => callback_condition = Some(BpCondition::Python(expressions::preprocess_python_expr(expr))),
+                ExprType::Rhai => callback_condition = Some(BpCondition::Rhai(expr.to_owned())),
+            }
+        }
+        wp.set_condition(native_condition.as_deref().unwrap_or(""));
+
+        // ">N" and bare "N" map onto LLDB's native ignore count; "==N" and "%N" have no native
+        // equivalent and need a stateful counting callback instead, same split as `init_bp_actions`.
+        let counting_condition = match bp_info.hit_condition.as_ref().and_then(|s| parse_hit_condition(s)) {
+            Some(HitCondition::GreaterThan(n)) => {
+                wp.set_ignore_count(n);
+                None
+            }
+            other => {
+                wp.set_ignore_count(0);
+                other
+            }
+        };
+
+        if callback_condition.is_none() && counting_condition.is_none() {
+            wp.clear_callback();
+            return;
+        }
+
+        let hit_count = Rc::new(Cell::new(0u32));
+
+        wp.set_callback(move |process| {
+            if let Some(ref condition) = callback_condition {
+                let satisfied = match condition {
+                    BpCondition::Python(expr) => evaluate_python_wp_condition(expr, process),
+                    BpCondition::Rhai(expr) => evaluate_rhai_wp_condition(expr, process),
+                };
+                if !satisfied {
+                    return false;
+                }
+            }
+            if let Some(counting_condition) = counting_condition {
+                let count = hit_count.get() + 1;
+                hit_count.set(count);
+                let satisfied = match counting_condition {
+                    HitCondition::Equal(n) => count == n,
+                    HitCondition::Modulo(n) => n != 0 && count % n == 0,
+                    HitCondition::GreaterThan(_) => true, // handled via set_ignore_count above
+                };
+                if !satisfied {
+                    return false;
+                }
+            }
+            true
+        });
+    }
+
     fn init_bp_actions(&self, bp: &mut SBBreakpoint, bp_info: &BreakpointInfo) {
         fn evaluate_python_bp_condition(
             expr: &str, process: &SBProcess, thread: &SBThread, location: &SBBreakpointLocation,
@@ -507,27 +1054,113 @@ impl DebugSession {
             }
         }
 
+        // Renders a log message template (`Value is {x.y}, flag is {flag}`) by evaluating each
+        // `{expr}` fragment in the stopped frame's context and substituting its rendered value.
+        fn render_log_message(template: &str, process: &SBProcess, thread: &SBThread) -> String {
+            let interpreter = process.target().debugger().command_interpreter();
+            let context = SBExecutionContext::from_frame(&thread.frame_at_index(0));
+            expressions::interpolate_message(template, |expr| {
+                let pp_expr = expressions::preprocess_simple_expr(expr);
+                match python::evaluate(&interpreter, &pp_expr, true, &context) {
+                    Ok(PythonValue::SBValue(sbval)) => sbval
+                        .summary()
+                        .map(into_string_lossy)
+                        .or_else(|| sbval.value().map(into_string_lossy))
+                        .unwrap_or_else(|| "<no value>".to_owned()),
+                    Ok(PythonValue::Bool(val)) => val.to_string(),
+                    Ok(PythonValue::Int(val)) => val.to_string(),
+                    Ok(PythonValue::String(val)) => val,
+                    _ => "<error>".to_owned(),
+                }
+            })
+        }
+
+        fn evaluate_rhai_bp_condition(expr: &str, _process: &SBProcess, thread: &SBThread, _location: &SBBreakpointLocation) -> bool {
+            let frame = thread.frame_at_index(0);
+            match rhai_support::evaluate(&frame, expr) {
+                Ok(PythonValue::SBValue(val)) => val.try_value_as_unsigned().map(|v| v != 0).unwrap_or(true),
+                Ok(PythonValue::Bool(val)) => val,
+                Ok(PythonValue::Int(val)) => val != 0,
+                _ => true,
+            }
+        }
+
+        // "Native" conditions (no `/py`/`/se`/`/rhai` prefix) are handed to LLDB's own
+        // SetCondition, which gates stops before our callback even runs, so it composes for free
+        // with the hit-count/logpoint callback below. Simple/Python/Rhai conditions need their
+        // respective evaluators, so they're folded into that callback instead.
+        let mut native_condition = None;
+        let mut callback_condition = None;
         if let Some(ref condition) = bp_info.condition {
             let (expr, ty) = self.get_expression_type(condition);
             match ty {
-                ExprType::Native => bp.set_condition(expr),
-                ExprType::Simple => {
-                    let pp_expr = expressions::preprocess_simple_expr(expr);
-                    bp.set_callback(move |process, thread, location| {
-                        evaluate_python_bp_condition(&pp_expr, process, thread, location)
-                    });
-                }
-                ExprType::Python => {
-                    let pp_expr = expressions::preprocess_python_expr(expr);
-                    bp.set_callback(move |process, thread, location| {
-                        evaluate_python_bp_condition(&pp_expr, process, thread, location)
-                    });
-                }
+                ExprType::Native => native_condition = Some(expr.to_owned()),
+                ExprType::Simple => callback_condition = Some(BpCondition::Python(expressions::preprocess_simple_expr(expr))),
+                ExprType::Python => callback_condition = Some(BpCondition::Python(expressions::preprocess_python_expr(expr))),
+                ExprType::Rhai => callback_condition = Some(BpCondition::Rhai(expr.to_owned())),
             }
-        } else {
+        }
+        bp.set_condition(native_condition.as_deref().unwrap_or(""));
+
+        // ">N" and bare "N" map onto LLDB's native ignore count; "==N" and "%N" have no native
+        // equivalent and need a stateful counting callback instead.
+        let counting_condition = match bp_info.hit_condition.as_ref().and_then(|s| parse_hit_condition(s)) {
+            Some(HitCondition::GreaterThan(n)) => {
+                bp.set_ignore_count(n);
+                None
+            }
+            other => {
+                bp.set_ignore_count(0);
+                other
+            }
+        };
+
+        let log_message = bp_info.log_message.clone();
+
+        if callback_condition.is_none() && counting_condition.is_none() && log_message.is_none() {
             bp.clear_callback();
+            return;
         }
-        // TODO: hit count & log_message
+
+        let hit_count = Rc::new(Cell::new(0u32));
+        let send_message = self.send_message.clone();
+
+        bp.set_callback(move |process, thread, location| {
+            if let Some(ref condition) = callback_condition {
+                let satisfied = match condition {
+                    BpCondition::Python(expr) => evaluate_python_bp_condition(expr, process, thread, location),
+                    BpCondition::Rhai(expr) => evaluate_rhai_bp_condition(expr, process, thread, location),
+                };
+                if !satisfied {
+                    return false;
+                }
+            }
+            if let Some(counting_condition) = counting_condition {
+                let count = hit_count.get() + 1;
+                hit_count.set(count);
+                let satisfied = match counting_condition {
+                    HitCondition::Equal(n) => count == n,
+                    HitCondition::Modulo(n) => n != 0 && count % n == 0,
+                    HitCondition::GreaterThan(_) => true, // handled via set_ignore_count above
+                };
+                if !satisfied {
+                    return false;
+                }
+            }
+            if let Some(ref template) = log_message {
+                let text = render_log_message(template, process, thread);
+                let mut send_message = send_message.clone();
+                let _ = send_message.try_send(ProtocolMessage::Event(Event {
+                    seq: 0,
+                    body: EventBody::output(OutputEventBody {
+                        output: format!("{}\n", text),
+                        ..Default::default()
+                    }),
+                }));
+                return false;
+            }
+            true
+        });
     }
 
     fn is_valid_source_bp_location(&self, bp_loc: &SBBreakpointLocation, bp_info: &mut BreakpointInfo) -> bool {
@@ -542,20 +1175,32 @@ impl DebugSession {
         true
     }
 
-    fn handle_launch(&mut self, args: LaunchRequestArguments) -> Result<Box<AsyncResponder>, Error> {
+    fn handle_launch(&mut self, args: LaunchRequestArguments, request_seq: u32) -> Result<Box<AsyncResponder>, Error> {
         if let Some(commands) = &args.init_commands {
             self.exec_commands(&commands);
         }
+        self.restart_source = Some(RestartSource::Launch(args.clone()));
         self.target = Initialized(self.create_target(&args.program)?);
         self.disassembly = Initialized(disassembly::AddressSpace::new(&self.target));
         self.send_event(EventBody::initialized);
-        Ok(Box::new(move |s: &mut DebugSession| s.complete_launch(args)))
+        Ok(Box::new(move |s: &mut DebugSession| s.complete_launch(args, request_seq)))
     }
 
-    fn complete_launch(&mut self, args: LaunchRequestArguments) -> Result<ResponseBody, Error> {
+    fn complete_launch(&mut self, args: LaunchRequestArguments, request_seq: u32) -> Result<ResponseBody, Error> {
         if let Some(ref commands) = args.pre_run_commands {
             self.exec_commands(commands);
         }
+
+        // "console": "integratedTerminal"/"externalTerminal" - the debuggee is spawned by the
+        // client's terminal, not by us, so we can't know its pid until the client answers our
+        // `runInTerminal` reverse request. Park the launch response until then.
+        match &args.terminal {
+            Some(TerminalKind::Integrated) | Some(TerminalKind::External) => {
+                return self.launch_in_terminal(args, request_seq);
+            }
+            _ => (),
+        }
+
         let mut launch_info = SBLaunchInfo::new();
 
         // TODO: Streaming iterator?
@@ -565,6 +1210,9 @@ impl DebugSession {
         if let Some(ref ds) = args.display_settings {
             self.update_display_settings(ds);
         }
+        if let Some(secs) = args.interrupt_timeout {
+            self.interrupt_timeout = Duration::from_millis((secs * 1000.0).max(0.0) as u64);
+        }
         if let Some(ref args) = args.args {
             launch_info.set_arguments(args.iter().map(|a| a.as_ref()), false);
         }
@@ -601,27 +1249,93 @@ impl DebugSession {
         Ok(ResponseBody::launch)
     }
 
-    fn run_in_vscode_terminal(&mut self, terminal_kind: TerminalKind, mut args: Vec<String>) {
-        let terminal_kind = match terminal_kind {
-            TerminalKind::External => "external",
-            TerminalKind::Integrated => {
-                args.insert(0, "\n".into());
-                "integrated"
-            }
+    // Ask the client to spawn the debuggee command line in its integrated/external terminal, and
+    // park the launch response until the client tells us what pid it ended up with.
+    fn launch_in_terminal(&mut self, args: LaunchRequestArguments, request_seq: u32) -> Result<ResponseBody, Error> {
+        let terminal_kind = match &args.terminal {
+            Some(TerminalKind::External) => "external",
+            Some(TerminalKind::Integrated) => "integrated",
             _ => unreachable!(),
-        };
+        }
+        .to_owned();
+
+        let mut command_line = vec![args.program.clone()];
+        command_line.extend(args.args.iter().flatten().cloned());
+
         let req_args = RunInTerminalRequestArguments {
-            args: args,
-            cwd: String::new(),
-            env: None,
+            args: command_line,
+            cwd: args.cwd.clone().unwrap_or_default(),
+            env: args.env.clone(),
             kind: Some(terminal_kind.to_owned()),
             title: Some("Debuggee".to_owned()),
         };
         self.send_request(RequestArguments::runInTerminal(req_args));
+        let reverse_request_seq = self.request_seq - 1;
+
+        self.pending_requests.insert(
+            reverse_request_seq,
+            PendingRequest::RunInTerminal {
+                launch_request_seq: request_seq,
+                args,
+            },
+        );
+        self.deferred_response_pending = true;
+        // Discarded by `handle_configuration_done` - the real response is sent from
+        // `handle_response` once the client's reply lets us attach to the debuggee.
+        Ok(ResponseBody::launch)
     }
 
     fn handle_attach(&mut self, args: AttachRequestArguments) -> Result<Box<AsyncResponder>, Error> {
-        unimplemented!()
+        if let Some(ref commands) = args.init_commands {
+            self.exec_commands(&commands);
+        }
+        self.restart_source = Some(RestartSource::Attach(args.clone()));
+        let executable = args.program.as_deref().unwrap_or("");
+        self.target = Initialized(self.create_target(executable)?);
+        self.disassembly = Initialized(disassembly::AddressSpace::new(&self.target));
+        self.send_event(EventBody::initialized);
+        Ok(Box::new(move |s: &mut DebugSession| s.complete_attach(args)))
+    }
+
+    fn complete_attach(&mut self, args: AttachRequestArguments) -> Result<ResponseBody, Error> {
+        if let Some(ref commands) = args.pre_run_commands {
+            self.exec_commands(commands);
+        }
+        if let Some(secs) = args.interrupt_timeout {
+            self.interrupt_timeout = Duration::from_millis((secs * 1000.0).max(0.0) as u64);
+        }
+
+        // Unlike `launch`, the debuggee already exists (or lives on a remote gdb-server) - we
+        // attach/connect to it rather than spawning it, and `process_launched` stays false so
+        // `handle_disconnect` detaches instead of killing it.
+        if let Some(ref remote) = args.gdb_remote {
+            let url = format!("connect://{}:{}", remote.host, remote.port);
+            match self.target.connect_remote(&self.event_listener, &url, "gdb-remote") {
+                Ok(process) => self.process = Initialized(process),
+                Err(error) => return Err(error.into()),
+            }
+        } else if let Some(pid) = args.pid {
+            let error = self.target.attach_to_process_with_id(&self.event_listener, pid as u64);
+            if !error.is_success() {
+                return Err(error.into());
+            }
+            self.process = Initialized(self.target.process());
+        } else if let Some(ref name) = args.program {
+            let error = self.target.attach_to_process_with_name(&self.event_listener, name, false);
+            if !error.is_success() {
+                return Err(error.into());
+            }
+            self.process = Initialized(self.target.process());
+        } else {
+            return Err(Error::UserError("Must specify one of `pid`, `program`, or `gdbRemote` to attach".into()));
+        }
+        self.process_launched = false;
+
+        if let Some(commands) = args.post_run_commands {
+            self.exec_commands(&commands);
+        }
+        self.exit_commands = args.exit_commands;
+        Ok(ResponseBody::attach)
     }
 
     fn create_target(&self, program: &str) -> Result<SBTarget, Error> {
@@ -644,19 +1358,15 @@ impl DebugSession {
         Ok(target)
     }
 
+    // Only reached for `"console": "internalConsole"` (or no `terminal` at all) - integrated and
+    // external terminal launches are handled by `launch_in_terminal` before we get here.
     fn configure_stdio(&mut self, args: &LaunchRequestArguments, launch_info: &mut SBLaunchInfo) -> Result<(), Error> {
         let tty_name = match args.terminal {
             Some(ref terminal_kind) => {
                 if cfg!(unix) {
                     // use selected platform instead of cfg
                     match terminal_kind {
-                        TerminalKind::External | TerminalKind::Integrated => {
-                            let terminal =
-                                Terminal::create(|args| self.run_in_vscode_terminal(terminal_kind.clone(), args))?;
-                            let tty_name = terminal.tty_name().to_owned();
-                            self.terminal = Some(terminal);
-                            Some(tty_name)
-                        }
+                        TerminalKind::External | TerminalKind::Integrated => unreachable!(),
                         TerminalKind::Console => None,
                     }
                 } else {
@@ -708,7 +1418,20 @@ impl DebugSession {
     fn handle_configuration_done(&mut self) -> Result<(), Error> {
         if let Some((request_seq, mut responder)) = self.on_configuration_done.take() {
             let result = responder.call_box((self,));
-            self.send_response(request_seq, result);
+            if self.deferred_response_pending {
+                // `complete_launch` parked the real response on a `runInTerminal` round-trip;
+                // `handle_response` will deliver it once the client tells us the debuggee's pid.
+                self.deferred_response_pending = false;
+            } else {
+                self.send_response(request_seq, result);
+            }
+        }
+        if let Some(source) = self.pending_relaunch.take() {
+            // Unlike launch/attach, `restart` already sent its own response - there's nothing to
+            // reply to here, just the parked re-launch/re-attach to run.
+            if let Err(err) = self.do_relaunch(source) {
+                self.console_error(format!("Failed to restart: {}", err));
+            }
         }
         Ok(())
     }
@@ -724,72 +1447,86 @@ impl DebugSession {
         Ok(response)
     }
 
+    // Resolves frames only up to the end of the `[start_frame, start_frame + levels)` window the
+    // client actually asked for (frames before the window that aren't cached yet are resolved
+    // too, since the cache is a contiguous prefix of the stack), and remembers what it resolved in
+    // `stack_frame_cache` so scrolling through the same thread's stack trace a page at a time
+    // doesn't re-symbolicate (and re-map source paths for) frames it already resolved earlier in
+    // this stop. `before_resume` clears the whole cache,
+    // since both the frames and the `var_refs` handles they carry belong to the stop that ended.
     fn handle_stack_trace(&mut self, args: StackTraceArguments) -> Result<StackTraceResponseBody, Error> {
-        let thread = self
-            .process
-            .thread_by_id(args.thread_id as ThreadID)
-            .expect("Invalid thread id");
+        let thread_id = args.thread_id as ThreadID;
+        let thread = self.process.thread_by_id(thread_id).expect("Invalid thread id");
+        let total_frames = thread.num_frames() as usize;
 
-        let start_frame = args.start_frame.unwrap_or(0);
-        let levels = args.levels.unwrap_or(std::i64::MAX);
+        let start_frame = args.start_frame.unwrap_or(0).max(0) as usize;
+        let levels = args.levels.unwrap_or(std::i64::MAX).max(0) as usize;
+        let end_frame = total_frames.min(start_frame.saturating_add(levels));
 
-        let mut stack_frames = vec![];
-        for i in start_frame..(start_frame + levels) {
-            let frame = thread.frame_at_index(i as u32);
+        let mut cache = self.stack_frame_cache.remove(&thread_id).unwrap_or_default();
+        while cache.len() < end_frame {
+            let frame = thread.frame_at_index(cache.len() as u32);
             if !frame.is_valid() {
                 break;
             }
+            cache.push(self.resolve_stack_frame(&frame));
+        }
+        let stack_frames = cache[start_frame.min(cache.len())..end_frame.min(cache.len())].to_vec();
+        self.stack_frame_cache.insert(thread_id, cache);
 
-            let handle = self
-                .var_refs
-                .create(None, "[frame]", Container::StackFrame(frame.clone()));
-            let mut stack_frame: StackFrame = Default::default();
+        Ok(StackTraceResponseBody {
+            stack_frames: stack_frames,
+            total_frames: Some(total_frames as i64),
+        })
+    }
 
-            stack_frame.id = handle.get() as i64;
-            let pc_address = frame.pc_address();
-            stack_frame.name = if let Some(name) = frame.function_name() {
-                name.to_owned()
-            } else {
-                format!("{:X}", pc_address.file_address())
-            };
+    // Symbolicates one frame and maps its source location to a local path - the work
+    // `handle_stack_trace` defers for as many frames as it can get away with.
+    fn resolve_stack_frame(&mut self, frame: &SBFrame) -> StackFrame {
+        let handle = self
+            .var_refs
+            .create(None, "[frame]", Container::StackFrame(frame.clone()));
+        let mut stack_frame: StackFrame = Default::default();
+
+        stack_frame.id = handle.get() as i64;
+        let pc_address = frame.pc_address();
+        stack_frame.name = if let Some(name) = frame.function_name() {
+            name.to_owned()
+        } else {
+            format!("{:X}", pc_address.file_address())
+        };
 
-            if !self.in_disassembly(&frame) {
-                if let Some(le) = frame.line_entry() {
-                    let fs = le.file_spec();
-                    if let Some(local_path) = self.map_filespec_to_local(&fs) {
-                        stack_frame.line = le.line() as i64;
-                        stack_frame.column = le.column() as i64;
-                        stack_frame.source = Some(Source {
-                            name: Some(fs.filename().to_owned()),
-                            path: Some(local_path.as_ref().clone()),
-                            ..Default::default()
-                        });
-                    }
+        if !self.in_disassembly(frame) {
+            if let Some(le) = frame.line_entry() {
+                let fs = le.file_spec();
+                if let Some(local_path) = self.map_filespec_to_local(&fs) {
+                    stack_frame.line = le.line() as i64;
+                    stack_frame.column = le.column() as i64;
+                    stack_frame.source = Some(Source {
+                        name: Some(fs.filename().to_owned()),
+                        path: Some(local_path.as_ref().clone()),
+                        ..Default::default()
+                    });
                 }
-            } else {
-                let pc_addr = frame.pc_address();
-                let dasm = match self.disassembly.get_by_address(&pc_addr) {
-                    Some(dasm) => dasm,
-                    None => {
-                        debug!("Creating disassembly for {:?}", pc_addr);
-                        self.disassembly.create_from_address(&pc_addr)
-                    }
-                };
-                stack_frame.line = dasm.line_num_by_address(pc_addr.load_address(&self.target)) as i64;
-                stack_frame.column = 0;
-                stack_frame.source = Some(Source {
-                    name: Some(dasm.source_name().to_owned()),
-                    source_reference: Some(handles::to_i64(Some(dasm.handle()))),
-                    ..Default::default()
-                });
             }
-            stack_frames.push(stack_frame);
+        } else {
+            let pc_addr = frame.pc_address();
+            let dasm = match self.disassembly.get_by_address(&pc_addr) {
+                Some(dasm) => dasm,
+                None => {
+                    debug!("Creating disassembly for {:?}", pc_addr);
+                    self.disassembly.create_from_address(&pc_addr)
+                }
+            };
+            stack_frame.line = dasm.line_num_by_address(pc_addr.load_address(&self.target)) as i64;
+            stack_frame.column = 0;
+            stack_frame.source = Some(Source {
+                name: Some(dasm.source_name().to_owned()),
+                source_reference: Some(handles::to_i64(Some(dasm.handle()))),
+                ..Default::default()
+            });
         }
-
-        Ok(StackTraceResponseBody {
-            stack_frames: stack_frames,
-            total_frames: Some(thread.num_frames() as i64),
-        })
+        stack_frame
     }
 
     fn in_disassembly(&mut self, frame: &SBFrame) -> bool {
@@ -853,7 +1590,15 @@ impl DebugSession {
 
     fn handle_variables(&mut self, args: VariablesArguments) -> Result<VariablesResponseBody, Error> {
         let container_handle = handles::from_i64(args.variables_reference).unwrap();
+        let variables = self.expand_container(container_handle)?;
+        Ok(VariablesResponseBody { variables: variables })
+    }
 
+    // Lists the immediate children of `container_handle`, regardless of what kind of container it
+    // is (scope, struct/array, synthetic `[raw]` view, `expr[a:b]` range, ...). Factored out of
+    // `handle_variables` so `find_in_container` can walk the same hierarchy without going through
+    // a `VariablesArguments` request.
+    fn expand_container(&mut self, container_handle: Handle) -> Result<Vec<Variable>, Error> {
         if let Some(container) = self.var_refs.get(container_handle) {
             let variables = match container {
                 Container::Locals(frame) => {
@@ -917,9 +1662,42 @@ impl DebugSession {
                     }
                     variables
                 }
+                Container::Range(var, start, end) => {
+                    let var = var.clone();
+                    let (start, end) = (*start, *end);
+                    let mut vars_iter = (start..end).map(|i| var.child_at_index(i));
+                    self.convert_scope_values(&mut vars_iter, "", Some(container_handle))
+                }
+                Container::Grid(var, dims, offset) => {
+                    let var = var.clone();
+                    let (dims, offset) = (dims.clone(), *offset);
+                    if dims.len() <= 1 {
+                        let len = dims.first().copied().unwrap_or(0);
+                        let mut vars_iter = (offset..offset + len).map(|i| var.child_at_index(i as u32));
+                        self.convert_scope_values(&mut vars_iter, "", Some(container_handle))
+                    } else {
+                        let inner_dims = Rc::new(dims[1..].to_vec());
+                        let stride: usize = inner_dims.iter().product();
+                        (0..dims[0])
+                            .map(|i| {
+                                let row_handle = self.var_refs.create(
+                                    Some(container_handle),
+                                    &i.to_string(),
+                                    Container::Grid(var.clone(), inner_dims.clone(), offset + i * stride),
+                                );
+                                Variable {
+                                    name: format!("[{}]", i),
+                                    value: format!("{:?}", &inner_dims[..]),
+                                    variables_reference: handles::to_i64(Some(row_handle)),
+                                    ..Default::default()
+                                }
+                            })
+                            .collect()
+                    }
+                }
                 Container::StackFrame(_) => vec![],
             };
-            Ok(VariablesResponseBody { variables: variables })
+            Ok(variables)
         } else {
             Err(Error::Internal(format!(
                 "Invalid variabes reference: {}",
@@ -928,6 +1706,63 @@ impl DebugSession {
         }
     }
 
+    // Caps for `find_in_container`'s depth-first walk: object graphs with synthetic children or
+    // cyclic pointers (e.g. intrusive linked lists) can be effectively unbounded, so the walk
+    // bails out once either limit is hit rather than searching exhaustively.
+    const FIND_IN_VARIABLES_MATCH_CAP: usize = 200;
+    const FIND_IN_VARIABLES_MAX_DEPTH: u32 = 16;
+
+    fn handle_find_in_variables(&mut self, args: FindInVariablesArguments) -> Result<FindInVariablesResponseBody, Error> {
+        let container_handle = handles::from_i64(args.variables_reference).unwrap();
+
+        let matches: Box<Fn(&str, &str) -> bool> = if args.regex.unwrap_or(false) {
+            let re = regex::Regex::new(&args.query).map_err(|err| Error::UserError(format!("Invalid regex: {}", err)))?;
+            Box::new(move |name: &str, value: &str| re.is_match(name) || re.is_match(value))
+        } else {
+            let query = args.query.to_lowercase();
+            Box::new(move |name: &str, value: &str| name.to_lowercase().contains(&query) || value.to_lowercase().contains(&query))
+        };
+
+        let mut results = vec![];
+        let mut visited = HashSet::new();
+        self.find_in_container(container_handle, matches.as_ref(), &mut results, &mut visited, 0);
+        Ok(FindInVariablesResponseBody { variables: results })
+    }
+
+    // Depth-first walk over the container hierarchy rooted at `container_handle`, collecting
+    // every `Variable` whose name or displayed value satisfies `matches`. `visited` only skips a
+    // handle that has already been walked; every expansion creates fresh child handles, so a
+    // cyclic pointer graph is cut off by `FIND_IN_VARIABLES_MAX_DEPTH`, not by `visited`. The walk
+    // stops early once `FIND_IN_VARIABLES_MATCH_CAP` hits have accumulated or
+    // `FIND_IN_VARIABLES_MAX_DEPTH` is exceeded.
+    fn find_in_container(
+        &mut self, container_handle: Handle, matches: &Fn(&str, &str) -> bool, results: &mut Vec<Variable>,
+        visited: &mut HashSet<i64>, depth: u32,
+    ) {
+        if depth > Self::FIND_IN_VARIABLES_MAX_DEPTH || results.len() >= Self::FIND_IN_VARIABLES_MATCH_CAP {
+            return;
+        }
+        let children = match self.expand_container(container_handle) {
+            Ok(children) => children,
+            Err(_) => return,
+        };
+        for var in children {
+            if results.len() >= Self::FIND_IN_VARIABLES_MATCH_CAP {
+                return;
+            }
+            let is_match = matches(&var.name, &var.value);
+            let child_ref = var.variables_reference;
+            if is_match {
+                results.push(var);
+            }
+            if child_ref != 0 && visited.insert(child_ref) {
+                if let Some(child_handle) = handles::from_i64(child_ref) {
+                    self.find_in_container(child_handle, matches, results, visited, depth + 1);
+                }
+            }
+        }
+    }
+
     fn compose_container_eval_name(&self, container_handle: Handle) -> String {
         let mut eval_name = String::new();
         let mut container_handle = Some(container_handle);
@@ -966,12 +1801,20 @@ impl DebugSession {
                 })
             };
 
+            let load_address = var.load_address();
+            let memory_reference = if load_address != INVALID_ADDRESS {
+                Some(format!("0x{:x}", load_address))
+            } else {
+                None
+            };
+
             let variable = Variable {
                 name: name.to_owned(),
                 value: value,
                 type_: dtype.map(|v| v.to_owned()),
                 variables_reference: handles::to_i64(handle),
                 evaluate_name: eval_name,
+                memory_reference: memory_reference,
                 ..Default::default()
             };
 
@@ -1034,7 +1877,9 @@ impl DebugSession {
             Some(s) => s,
             None => {
                 if is_container {
-                    if self.container_summary {
+                    if let Some(provider) = self.find_summary_provider(var) {
+                        self.render_summary_provider(provider, var)
+                    } else if self.container_summary {
                         self.get_container_summary(var)
                     } else {
                         "{...}".to_owned()
@@ -1048,12 +1893,20 @@ impl DebugSession {
         value_str
     }
 
+    const DEFAULT_SUMMARY_MAX_LENGTH: usize = 32;
+
     fn get_container_summary(&self, var: &SBValue) -> String {
-        const MAX_LENGTH: usize = 32;
+        self.render_container_summary(var, Self::DEFAULT_SUMMARY_MAX_LENGTH, usize::max_value())
+    }
 
+    fn render_container_summary(&self, var: &SBValue, max_length: usize, max_children: usize) -> String {
         let mut summary = String::from("{");
         let mut empty = true;
-        for child in var.children() {
+        for (index, child) in var.children().enumerate() {
+            if index >= max_children {
+                summary.push_str(", ...");
+                break;
+            }
             if let Some(name) = child.name() {
                 if let Some(Ok(value)) = child.value().map(|s| s.to_str()) {
                     if empty {
@@ -1070,7 +1923,7 @@ impl DebugSession {
                 }
             }
 
-            if summary.len() > MAX_LENGTH {
+            if summary.len() > max_length {
                 summary.push_str(", ...");
                 break;
             }
@@ -1082,6 +1935,61 @@ impl DebugSession {
         summary
     }
 
+    // Finds the most recently registered provider whose `type_regex` matches `var`'s type name
+    // (see `register_summary_providers` - later registrations take priority, so providers can be
+    // layered/overridden without removing earlier ones).
+    fn find_summary_provider(&self, var: &SBValue) -> Option<&SummaryProvider> {
+        let type_name = var.type_name()?;
+        self.summary_providers.iter().rev().find(|p| p.type_regex.is_match(type_name))
+    }
+
+    fn render_summary_provider(&self, provider: &SummaryProvider, var: &SBValue) -> String {
+        let rendered = if let Some(ref template) = provider.template {
+            self.render_summary_template(template, var)
+        } else if let Some(ref script) = provider.script {
+            self.render_summary_script(script, var)
+        } else {
+            return self.render_container_summary(var, provider.max_length, provider.max_children);
+        };
+        truncate_with_ellipsis(rendered, provider.max_length)
+    }
+
+    // Expands each `{$.field}` in `template` into `get_var_value_str` of that child member of
+    // `var` - e.g. `size={$.size} cap={$.capacity}`.
+    fn render_summary_template(&self, template: &str, var: &SBValue) -> String {
+        let mut out = String::new();
+        let mut rest = template;
+        while let Some(start) = rest.find("{$.") {
+            out.push_str(&rest[..start]);
+            let after = &rest[start + 3..];
+            match after.find('}') {
+                Some(end) => {
+                    let child = var.child_member_with_name(&after[..end]);
+                    out.push_str(&self.get_var_value_str(&child, Format::Default, false));
+                    rest = &after[end + 1..];
+                }
+                None => {
+                    out.push_str(&rest[start..]);
+                    rest = "";
+                }
+            }
+        }
+        out.push_str(rest);
+        out
+    }
+
+    // Runs `script` through the sandboxed Rhai evaluator with `$` bound to `var` (see
+    // `rhai_support::evaluate_value`) and renders whatever it returns.
+    fn render_summary_script(&self, script: &str, var: &SBValue) -> String {
+        match rhai_support::evaluate_value(var, script) {
+            Ok(PythonValue::SBValue(val)) => self.get_var_value_str(&val, Format::Default, false),
+            Ok(PythonValue::String(s)) | Ok(PythonValue::Object(s)) => s,
+            Ok(PythonValue::Int(v)) => v.to_string(),
+            Ok(PythonValue::Bool(v)) => v.to_string(),
+            Err(err) => format!("<error: {}>", err),
+        }
+    }
+
     fn get_expr_format<'a>(&self, expr: &'a str) -> (&'a str, Option<Format>) {
         let mut chars = expr.chars();
         if let Some(ch) = chars.next_back() {
@@ -1106,6 +2014,55 @@ impl DebugSession {
         (expr, None)
     }
 
+    // Detects a trailing `,[rows,cols]` / `,[n]` reshape spec (as opposed to the single-letter
+    // format codes `get_expr_format` handles), returning the base expression and the parsed shape.
+    fn get_expr_reshape<'a>(&self, expr: &'a str) -> (&'a str, Option<Vec<usize>>) {
+        let trimmed = expr.trim_end();
+        if !trimmed.ends_with(']') {
+            return (expr, None);
+        }
+        if let Some(open) = trimmed.rfind(",[") {
+            let base = &trimmed[..open];
+            let inside = &trimmed[open + 2..trimmed.len() - 1];
+            let dims: Option<Vec<usize>> = inside.split(',').map(|d| d.trim().parse().ok()).collect();
+            if let Some(dims) = dims {
+                if !dims.is_empty() && dims.iter().all(|&d| d > 0) {
+                    return (base, Some(dims));
+                }
+            }
+        }
+        (expr, None)
+    }
+
+    // Resolves `expr,[rows,cols]` (see `get_expr_reshape`) into a synthetic row-major N-D grid
+    // view over `expr`'s elements, so it shows up in the Variables pane as nested `[i][j]` cells.
+    // Pointers are treated as flat buffers of the pointee type (no `num_children` to validate
+    // against), matching how `float*,[4,4]` lets a user view a raw buffer as a matrix.
+    fn evaluate_reshape(&mut self, expr: &str, shape: Vec<usize>, frame: Option<&SBFrame>) -> Result<EvaluateResponseBody, Error> {
+        let base = match self.evaluate_expr_in_frame(expr, frame)? {
+            PythonValue::SBValue(val) => val,
+            _ => return Err(Error::UserError(format!("`{}` is not indexable", expr))),
+        };
+
+        let is_pointer = base.type_().type_class().intersects(TypeClass::Pointer | TypeClass::Reference);
+        let count = base.num_children();
+        let element_count: usize = shape.iter().product();
+        if !is_pointer && (count == 0 || element_count > count as usize) {
+            return Err(Error::UserError(format!(
+                "Shape {:?} ({} elements) does not fit within `{}` ({} elements)",
+                shape, element_count, expr, count
+            )));
+        }
+
+        let name = format!("{:?}", shape);
+        let handle = self.var_refs.create(None, &name, Container::Grid(base, Rc::new(shape), 0));
+        Ok(EvaluateResponseBody {
+            result: name,
+            variables_reference: handles::to_i64(Some(handle)),
+            ..Default::default()
+        })
+    }
+
     fn handle_evaluate(&mut self, args: EvaluateArguments) -> Result<EvaluateResponseBody, Error> {
         let frame: Option<&SBFrame> = args.frame_id.map(|id| {
             let handle = handles::from_i64(id).unwrap();
@@ -1140,6 +2097,18 @@ impl DebugSession {
         // Expression
         let (expression, expr_format) = self.get_expr_format(expression);
         let expr_format = expr_format.unwrap_or(self.global_format);
+
+        // `expr[a:b]` can't be expressed as a single `PythonValue`, so it's resolved here rather
+        // than inside `evaluate_expr_in_frame` (see `evaluate_slice`).
+        if let Some((base_expr, Subscript::Slice(start, end))) = parse_trailing_subscript(expression) {
+            return self.evaluate_slice(base_expr, start, end, frame);
+        }
+
+        let (expression, reshape) = self.get_expr_reshape(expression);
+        if let Some(shape) = reshape {
+            return self.evaluate_reshape(expression, shape, frame);
+        }
+
         self.evaluate_expr_in_frame(expression, frame).map(|val| match val {
             PythonValue::SBValue(sbval) => {
                 let handle = self.get_var_handle(None, expression, &sbval);
@@ -1165,12 +2134,63 @@ impl DebugSession {
         })
     }
 
+    // Resolves a trailing `expr[a:b]` slice (see `parse_trailing_subscript`) into a synthetic
+    // `Container::Range` handle, so it shows up in the Variables pane named "[a..b]" the same way
+    // a struct field or array element would.
+    fn evaluate_slice(
+        &mut self, base_expr: &str, start: Option<i64>, end: Option<i64>, frame: Option<&SBFrame>,
+    ) -> Result<EvaluateResponseBody, Error> {
+        let base = match self.evaluate_expr_in_frame(base_expr, frame)? {
+            PythonValue::SBValue(val) => val,
+            _ => return Err(Error::UserError(format!("`{}` is not indexable", base_expr))),
+        };
+        let count = base.num_children();
+        if count == 0 {
+            return Err(Error::UserError(format!("`{}` is not indexable", base_expr)));
+        }
+        let (start, end) = resolve_slice_bounds(count, start, end).ok_or_else(|| {
+            Error::UserError(format!("Slice is out of range for `{}` ({} elements)", base_expr, count))
+        })?;
+
+        let name = format!("[{}..{}]", start, end);
+        let handle = self.var_refs.create(None, &name, Container::Range(base, start, end));
+        Ok(EvaluateResponseBody {
+            result: name,
+            variables_reference: handles::to_i64(Some(handle)),
+            ..Default::default()
+        })
+    }
+
     // Evaluates expr in the context of frame (or in global context if frame is None)
     // Returns expressions.Value or SBValue on success, SBError on failure.
     fn evaluate_expr_in_frame(&self, expr: &str, frame: Option<&SBFrame>) -> Result<PythonValue, Error> {
         let (expr, ty) = self.get_expression_type(expr);
         match ty {
             ExprType::Native => {
+                // `expr[-k]` resolves against the base value's `num_children()`, since LLDB's own
+                // expression parser only understands non-negative indices.
+                if let Some((base_expr, Subscript::Index(index))) = parse_trailing_subscript(expr) {
+                    let base = match frame {
+                        Some(frame) => frame.evaluate_expression(base_expr),
+                        None => self.target.evaluate_expression(base_expr),
+                    };
+                    let error = base.error();
+                    if !error.is_success() {
+                        return Err(error.into());
+                    }
+                    let count = base.num_children();
+                    if count == 0 {
+                        return Err(Error::UserError(format!("`{}` is not indexable", base_expr)));
+                    }
+                    return match resolve_index(count, index) {
+                        Some(i) => Ok(PythonValue::SBValue(base.child_at_index(i))),
+                        None => Err(Error::UserError(format!(
+                            "Index {} is out of range for `{}` ({} elements)",
+                            index, base_expr, count
+                        ))),
+                    };
+                }
+
                 let result = match frame {
                     Some(frame) => frame.evaluate_expression(expr),
                     None => self.target.evaluate_expression(expr),
@@ -1200,6 +2220,17 @@ impl DebugSession {
                     Err(s) => Err(Error::UserError(s)),
                 }
             }
+            ExprType::Rhai => {
+                let owned_frame;
+                let frame = match frame {
+                    Some(frame) => frame,
+                    None => {
+                        owned_frame = self.process.selected_thread().frame_at_index(0);
+                        &owned_frame
+                    }
+                };
+                rhai_support::evaluate(frame, expr).map_err(Error::UserError)
+            }
         }
     }
 
@@ -1211,12 +2242,128 @@ impl DebugSession {
             (&expr[4..], ExprType::Python)
         } else if expr.starts_with("/se ") {
             (&expr[4..], ExprType::Simple)
+        } else if expr.starts_with("/rhai ") {
+            (&expr[6..], ExprType::Rhai)
         } else {
             // TODO: expressions config
             (expr, ExprType::Simple)
         }
     }
 
+    fn handle_read_memory(&mut self, args: ReadMemoryArguments) -> Result<ReadMemoryResponseBody, Error> {
+        let base_address = parse_memory_reference(&args.memory_reference)?;
+        let address = (base_address as i64 + args.offset.unwrap_or(0)) as u64;
+        let count = args.count as usize;
+
+        let mut buffer = vec![0u8; count];
+        let (bytes_read, error) = self.process.read_memory(address, &mut buffer);
+        if bytes_read == 0 && count > 0 && !error.is_success() {
+            return Err(error.into());
+        }
+        buffer.truncate(bytes_read);
+
+        Ok(ReadMemoryResponseBody {
+            address: format!("0x{:x}", address),
+            data: Some(base64::encode(&buffer)),
+            unreadable_bytes: if bytes_read < count { Some((count - bytes_read) as i64) } else { None },
+        })
+    }
+
+    fn handle_write_memory(&mut self, args: WriteMemoryArguments) -> Result<WriteMemoryResponseBody, Error> {
+        let base_address = parse_memory_reference(&args.memory_reference)?;
+        let address = (base_address as i64 + args.offset.unwrap_or(0)) as u64;
+        let data = base64::decode(&args.data).map_err(|_| Error::UserError("`data` is not valid base64".into()))?;
+
+        let bytes_written = self.process.write_memory(address, &data);
+        if bytes_written == 0 && !data.is_empty() {
+            return Err(Error::UserError(format!("Could not write memory at {}", args.memory_reference)));
+        }
+
+        Ok(WriteMemoryResponseBody {
+            bytes_written: Some(bytes_written as i64),
+            ..Default::default()
+        })
+    }
+
+    fn handle_completions(&mut self, args: CompletionsArguments) -> Result<CompletionsResponseBody, Error> {
+        let frame: Option<&SBFrame> = args.frame_id.map(|id| {
+            let handle = handles::from_i64(id).unwrap();
+            if let Some(Container::StackFrame(ref frame)) = self.var_refs.get(handle) {
+                frame
+            } else {
+                panic!("Invalid frameId");
+            }
+        });
+
+        let column = (args.column.max(1) - 1) as usize;
+        let text = args.text.get(..column).unwrap_or(&args.text);
+        let (expr, expr_type) = self.get_expression_type(text);
+        let prefix_offset = text.len() - expr.len();
+
+        let targets = match expr_type {
+            ExprType::Native => {
+                let interpreter = self.debugger.command_interpreter();
+                interpreter
+                    .handle_completion(expr, expr.len() as u32)
+                    .into_iter()
+                    .map(|m| CompletionItem {
+                        label: m.clone(),
+                        text: Some(m),
+                        start: Some(prefix_offset as i64),
+                        length: Some(expr.len() as i64),
+                        ..Default::default()
+                    })
+                    .collect()
+            }
+            ExprType::Simple | ExprType::Python | ExprType::Rhai => self.complete_expression(expr, prefix_offset, frame),
+        };
+
+        Ok(CompletionsResponseBody { targets })
+    }
+
+    // Completes a dotted member-access chain (`foo.bar.ba`) by evaluating everything up to the
+    // last `.`/`[` in the stopped frame and enumerating the resulting value's children; a bare
+    // identifier with no object prefix instead completes against the embedded Python
+    // interpreter's globals.
+    fn complete_expression(&self, expr: &str, start: usize, frame: Option<&SBFrame>) -> Vec<CompletionItem> {
+        let (object_expr, partial) = match expr.rfind(|c| c == '.' || c == '[') {
+            Some(idx) => (&expr[..idx], &expr[idx + 1..]),
+            None => ("", expr),
+        };
+
+        let mut items = vec![];
+        if object_expr.is_empty() {
+            let interpreter = self.debugger.command_interpreter();
+            let context = self.context_from_frame(frame);
+            for name in python::interpreter_globals(&interpreter, &context) {
+                if name.starts_with(partial) {
+                    items.push(CompletionItem {
+                        label: name.clone(),
+                        text: Some(name),
+                        start: Some(start as i64),
+                        length: Some(partial.len() as i64),
+                        ..Default::default()
+                    });
+                }
+            }
+        } else if let Ok(PythonValue::SBValue(sbval)) = self.evaluate_expr_in_frame(object_expr, frame) {
+            for child in sbval.children() {
+                if let Some(name) = child.name() {
+                    if name.starts_with(partial) {
+                        items.push(CompletionItem {
+                            label: name.to_owned(),
+                            text: Some(name.to_owned()),
+                            start: Some((start + object_expr.len() + 1) as i64),
+                            length: Some(partial.len() as i64),
+                            ..Default::default()
+                        });
+                    }
+                }
+            }
+        }
+        items
+    }
+
     fn execute_command_in_frame(&self, command: &str, frame: Option<&SBFrame>) -> SBCommandReturnObject {
         let context = self.context_from_frame(frame);
         let mut result = SBCommandReturnObject::new();
@@ -1242,15 +2389,44 @@ impl DebugSession {
         }
     }
 
+    fn default_interrupt_timeout() -> Duration {
+        Duration::from_secs(3)
+    }
+
+    // Requests the stop asynchronously and responds right away - LLDB's own `Stopped` event,
+    // once it arrives, is what actually notifies the client (see `notify_process_stopped`). We
+    // just remember when we asked, so `check_interrupt_timeout` can warn if the inferior never
+    // gets there (e.g. deep in a syscall with signals masked), instead of `pause` silently
+    // appearing to do nothing.
     fn handle_pause(&mut self, args: PauseArguments) -> Result<(), Error> {
         let error = self.process.stop();
         if error.is_success() {
+            self.interrupt_requested_at = Some(Instant::now());
             Ok(())
         } else {
             Err(Error::UserError(error.message().into()))
         }
     }
 
+    // Called from both `handle_debug_event` and `handle_message`, i.e. on any LLDB event or any
+    // inbound client message - not a true wall-clock timer (there's no periodic tick in this event
+    // loop to hang one off), but enough that a pending interrupt that never lands still surfaces
+    // promptly instead of silently waiting on one specific kind of arrival. Fires at most once per
+    // `pause`: the first time `interrupt_timeout` elapses, it clears `interrupt_requested_at`, so
+    // retrying is a fresh `pause` request.
+    fn check_interrupt_timeout(&mut self) {
+        if let Some(requested_at) = self.interrupt_requested_at {
+            if requested_at.elapsed() >= self.interrupt_timeout {
+                self.interrupt_requested_at = None;
+                self.console_error(format!(
+                    "The debuggee did not respond to the interrupt request within {}ms; it may be blocked in a \
+                     system call or have signals masked. Send `pause` again to retry, or stop the session to force it to terminate.",
+                    self.interrupt_timeout.as_secs() * 1000 + self.interrupt_timeout.subsec_millis() as u64
+                ));
+            }
+        }
+    }
+
     fn handle_continue(&mut self, args: ContinueArguments) -> Result<ContinueResponseBody, Error> {
         self.before_resume();
         let error = self.process.resume();
@@ -1263,14 +2439,26 @@ impl DebugSession {
         }
     }
 
+    // `granularity` (DAP's "statement"/"line"/"instruction") overrides the `in_disassembly`
+    // heuristic when the client asks for it explicitly: `instruction` always steps one
+    // instruction, even in a normal source view (useful for intrinsics/inlined code); `line`/
+    // `statement` always step at the source level, even while the frame is shown as disassembly.
+    fn step_by_instruction(&mut self, frame: &SBFrame, granularity: Option<SteppingGranularity>) -> bool {
+        match granularity {
+            Some(SteppingGranularity::Instruction) => true,
+            Some(SteppingGranularity::Line) | Some(SteppingGranularity::Statement) => false,
+            None => self.in_disassembly(frame),
+        }
+    }
+
     fn handle_next(&mut self, args: NextArguments) -> Result<(), Error> {
         self.before_resume();
         let thread = self.process.thread_by_id(args.thread_id as ThreadID)?;
         let frame = thread.frame_at_index(0);
-        if !self.in_disassembly(&frame) {
-            thread.step_over();
-        } else {
+        if self.step_by_instruction(&frame, args.granularity) {
             thread.step_instruction(true);
+        } else {
+            thread.step_over();
         }
         Ok(())
     }
@@ -1278,19 +2466,93 @@ impl DebugSession {
     fn handle_step_in(&mut self, args: StepInArguments) -> Result<(), Error> {
         self.before_resume();
         let thread = self.process.thread_by_id(args.thread_id as ThreadID)?;
+        // If the client picked a specific callee from a prior `stepInTargets` response, queue a
+        // thread plan that steps into that function by name rather than just "the next call".
+        if let Some(target_id) = args.target_id {
+            if let Some(target_name) = self.step_in_targets.get(&target_id) {
+                thread.step_into_named(target_name);
+                return Ok(());
+            }
+        }
         let frame = thread.frame_at_index(0);
-        if !self.in_disassembly(&frame) {
-            thread.step_into();
-        } else {
+        if self.step_by_instruction(&frame, args.granularity) {
             thread.step_instruction(false);
+        } else {
+            thread.step_into();
         }
         Ok(())
     }
 
+    // On a line with multiple calls (`f(g(), h())`), disassembles the line's instruction range,
+    // finds the `call`/`bl`/`blx` instructions, and resolves each one's target symbol so the
+    // client can offer the user a choice of which callee to step into. Indirect calls whose
+    // operand `resolve_call_target` recognizes as a register or memory operand are omitted.
+    fn handle_step_in_targets(&mut self, args: StepInTargetsArguments) -> Result<StepInTargetsResponseBody, Error> {
+        let frame_id = match Handle::new(args.frame_id as u32) {
+            Some(h) => h,
+            None => return Err(Error::Internal("Invalid frame_id".into())),
+        };
+        let frame = match self.var_refs.get(frame_id) {
+            Some(Container::StackFrame(frame)) => frame,
+            _ => return Err(Error::Internal("Invalid frame_id".into())),
+        };
+
+        self.step_in_targets.clear();
+        let mut targets = vec![];
+        if let Some(line_entry) = frame.line_entry() {
+            let line = line_entry.line();
+            let instructions = frame.function().get_instructions(&self.target);
+            let mut next_id = 1;
+            for instr in instructions.iter() {
+                if instr.line_entry(&self.target).map(|le| le.line()) != Some(line) {
+                    continue;
+                }
+                if !is_call_mnemonic(&instr.mnemonic(&self.target)) {
+                    continue;
+                }
+                if let Some(target_name) = self.resolve_call_target(&instr) {
+                    let id = next_id;
+                    next_id += 1;
+                    self.step_in_targets.insert(id, target_name.clone());
+                    targets.push(StepInTarget {
+                        id,
+                        label: target_name,
+                        line: Some(line),
+                        ..Default::default()
+                    });
+                }
+            }
+        }
+        Ok(StepInTargetsResponseBody { targets })
+    }
+
+    // The operand text of a direct call is a resolved symbol name/address; register- or
+    // memory-indirect calls ("*%rax", "[rax]", ...) have no statically known target, so they're
+    // left out of the `stepInTargets` list rather than reported with a made-up label. Indirect
+    // calls are recognized only by a `%`, `[` or leading `*` in the operand text; a bare register
+    // operand such as ARM's `blx r3` is not recognized and is listed with the operand as its label.
+    fn resolve_call_target(&self, instr: &SBInstruction) -> Option<String> {
+        let operands = instr.operands(&self.target);
+        if operands.contains('%') || operands.contains('[') || operands.trim_start().starts_with('*') {
+            return None;
+        }
+        let comment = instr.comment(&self.target);
+        if !comment.is_empty() {
+            Some(comment)
+        } else {
+            Some(operands)
+        }
+    }
+
     fn handle_step_out(&mut self, args: StepOutArguments) -> Result<(), Error> {
         self.before_resume();
         let thread = self.process.thread_by_id(args.thread_id as ThreadID)?;
-        thread.step_out();
+        let frame = thread.frame_at_index(0);
+        if self.step_by_instruction(&frame, args.granularity) {
+            thread.step_instruction(true);
+        } else {
+            thread.step_out();
+        }
         Ok(())
     }
 
@@ -1325,12 +2587,157 @@ impl DebugSession {
         Ok(())
     }
 
+    // Kills the current debuggee and, once it's actually gone, relaunches (or reattaches) the
+    // same `self.target` from the original `launch`/`attach` arguments. Reusing the target -
+    // rather than tearing the whole session down like `handle_disconnect` does - is what keeps
+    // breakpoints (which LLDB tracks on the target, not the process) and `self.source_map`/
+    // display settings (which just live on `self` and are never touched here) alive across the
+    // restart.
+    fn handle_restart(&mut self, _args: RestartArguments) -> Result<(), Error> {
+        if self.restart_source.is_none() {
+            return Err(Error::UserError(
+                "Cannot restart: the session wasn't started with `launch` or `attach`.".into(),
+            ));
+        }
+        match self.process {
+            Initialized(ref process) => {
+                self.pending_restart = true;
+                process.kill();
+            }
+            NotInitialized => {
+                // Nothing has ever run yet (e.g. a `restart` that races the initial launch) -
+                // there's no `Exited` event coming, so relaunch immediately.
+                self.relaunch()?;
+            }
+        }
+        Ok(())
+    }
+
+    // Runs once the previous debuggee is confirmed gone - either right away from `handle_restart`
+    // if nothing was running, or from `handle_process_event` once its `Exited`/`Detached` event
+    // arrives for a process we killed for this reason. Only sends `initialized` and parks the
+    // actual re-launch/re-attach behind the next `configurationDone`, mirroring `handle_launch`'s
+    // own gating, so breakpoints the client (re-)sends in reaction to `initialized` are guaranteed
+    // to land before the debuggee runs.
+    fn relaunch(&mut self) -> Result<(), Error> {
+        self.before_resume();
+        self.known_threads.clear();
+        let source = self.restart_source.clone().expect("restart_source must be set before calling relaunch");
+        self.pending_relaunch = Some(source);
+        self.send_event(EventBody::initialized);
+        Ok(())
+    }
+
+    // The actual re-launch/re-attach, run from `handle_configuration_done` once the client has
+    // had a chance to (re-)send breakpoints in response to `relaunch`'s `initialized` event.
+    fn do_relaunch(&mut self, source: RestartSource) -> Result<(), Error> {
+        match source {
+            RestartSource::Launch(mut args) => {
+                // Unlike the original `launch`, this doesn't re-spawn through the client's
+                // integrated/external terminal even if `args.terminal` asked for one - relaunch
+                // just re-runs the debuggee directly via LLDB.
+                args.terminal = None;
+                let mut launch_info = SBLaunchInfo::new();
+                let env: Vec<String> = env::vars().map(|(k, v)| format!("{}={}", k, v)).collect();
+                launch_info.set_environment_entries(env.iter().map(|s| s.as_ref()), true);
+                if let Some(ref cli_args) = args.args {
+                    launch_info.set_arguments(cli_args.iter().map(|a| a.as_ref()), false);
+                }
+                if let Some(ref env) = args.env {
+                    let env: Vec<String> = env.iter().map(|(k, v)| format!("{}={}", k, v)).collect();
+                    launch_info.set_environment_entries(env.iter().map(|s| s.as_ref()), true);
+                }
+                if let Some(ref cwd) = args.cwd {
+                    launch_info.set_working_directory(&cwd);
+                }
+                if let Some(stop_on_entry) = args.stop_on_entry {
+                    if stop_on_entry {
+                        launch_info.set_launch_flags(launch_info.launch_flags() | LaunchFlag::StopAtEntry);
+                    }
+                }
+                self.configure_stdio(&args, &mut launch_info);
+                launch_info.set_listener(&self.event_listener);
+                self.process = Initialized(self.target.launch(&launch_info)?);
+                self.process_launched = true;
+                if let Some(ref commands) = args.post_run_commands {
+                    self.exec_commands(commands);
+                }
+                self.exit_commands = args.exit_commands;
+            }
+            RestartSource::Attach(args) => {
+                if let Some(ref remote) = args.gdb_remote {
+                    let url = format!("connect://{}:{}", remote.host, remote.port);
+                    self.process = Initialized(self.target.connect_remote(&self.event_listener, &url, "gdb-remote")?);
+                } else if let Some(pid) = args.pid {
+                    let error = self.target.attach_to_process_with_id(&self.event_listener, pid as u64);
+                    if !error.is_success() {
+                        return Err(error.into());
+                    }
+                    self.process = Initialized(self.target.process());
+                } else if let Some(ref name) = args.program {
+                    let error = self.target.attach_to_process_with_name(&self.event_listener, name, false);
+                    if !error.is_success() {
+                        return Err(error.into());
+                    }
+                    self.process = Initialized(self.target.process());
+                } else {
+                    return Err(Error::UserError("Must specify one of `pid`, `program`, or `gdbRemote` to attach".into()));
+                }
+                self.process_launched = false;
+                if let Some(ref commands) = args.post_run_commands {
+                    self.exec_commands(commands);
+                }
+                self.exit_commands = args.exit_commands;
+            }
+        }
+        Ok(())
+    }
+
+    // Reports the debuggee as gone - as a plain termination, or, if a `restart` request killed
+    // it, with `restart: Some(...)` followed by `relaunch` (which sends `initialized` and parks the
+    // actual re-launch until the next `configurationDone`) so the client knows this is a restart
+    // cycle rather than the session ending.
+    fn finish_terminated(&mut self) {
+        if self.pending_restart {
+            self.pending_restart = false;
+            self.send_event(EventBody::terminated(TerminatedEventBody {
+                restart: Some(serde_json::Value::Bool(true)),
+            }));
+            if let Err(err) = self.relaunch() {
+                self.console_error(format!("Failed to restart: {}", err));
+            }
+        } else {
+            self.send_event(EventBody::terminated(TerminatedEventBody { restart: None }));
+        }
+    }
+
     fn handle_display_settings(&mut self, args: DisplaySettingsArguments) -> Result<(), Error> {
         self.update_display_settings(&args);
+        if let Some(ref specs) = args.add_summary_providers {
+            self.register_summary_providers(specs)?;
+        }
         self.refresh_client_display();
         Ok(())
     }
 
+    // Compiles each spec's type-name regex and appends it to `self.summary_providers` (see
+    // `find_summary_provider`). A bad regex fails the whole `displaySettings` request rather than
+    // silently dropping that one provider, so the user notices and fixes it.
+    fn register_summary_providers(&mut self, specs: &[SummaryProviderSpec]) -> Result<(), Error> {
+        for spec in specs {
+            let type_regex = regex::Regex::new(&spec.type_regex)
+                .map_err(|err| Error::UserError(format!("Invalid type regex `{}`: {}", spec.type_regex, err)))?;
+            self.summary_providers.push(SummaryProvider {
+                type_regex,
+                template: spec.template.clone(),
+                script: spec.script.clone(),
+                max_length: spec.max_length.unwrap_or(Self::DEFAULT_SUMMARY_MAX_LENGTH),
+                max_children: spec.max_children.unwrap_or_else(usize::max_value),
+            });
+        }
+        Ok(())
+    }
+
     fn update_display_settings(&mut self, args: &DisplaySettingsArguments) {
         self.global_format = match args.display_format {
             None => self.global_format,
@@ -1385,10 +2792,12 @@ impl DebugSession {
 
     fn before_resume(&mut self) {
         self.var_refs.reset();
+        self.stack_frame_cache.clear();
     }
 
     fn handle_debug_event(&mut self, event: SBEvent) {
         debug!("Debug event: {:?}", event);
+        self.check_interrupt_timeout();
         if let Some(process_event) = event.as_process_event() {
             self.handle_process_event(&process_event);
         } else if let Some(target_event) = event.as_target_event() {
@@ -1406,14 +2815,20 @@ impl DebugSession {
                     all_threads_continued: Some(true),
                     thread_id: 0,
                 })),
-                ProcessState::Stopped if !process_event.restarted() => self.notify_process_stopped(&process_event),
-                ProcessState::Crashed => self.notify_process_stopped(&process_event),
+                ProcessState::Stopped if !process_event.restarted() => {
+                    self.interrupt_requested_at = None;
+                    self.notify_process_stopped(&process_event);
+                }
+                ProcessState::Crashed => {
+                    self.interrupt_requested_at = None;
+                    self.notify_process_stopped(&process_event);
+                }
                 ProcessState::Exited => {
                     let exit_code = self.process.exit_status() as i64;
                     self.send_event(EventBody::exited(ExitedEventBody { exit_code }));
-                    self.send_event(EventBody::terminated(TerminatedEventBody { restart: None }));
+                    self.finish_terminated();
                 }
-                ProcessState::Detached => self.send_event(EventBody::terminated(TerminatedEventBody { restart: None })),
+                ProcessState::Detached => self.finish_terminated(),
                 _ => (),
             }
         }
@@ -1548,6 +2963,110 @@ impl DebugSession {
     }
 }
 
+// LLDB's sentinel for "this SBValue has no load address" (`LLDB_INVALID_ADDRESS`).
+const INVALID_ADDRESS: u64 = u64::max_value();
+
+// Parses a DAP `memoryReference` ("0x1234...", as handed out in `Variable::memory_reference`)
+// back into the address it encodes.
+fn parse_memory_reference(memory_reference: &str) -> Result<u64, Error> {
+    let hex = memory_reference.trim_start_matches("0x");
+    u64::from_str_radix(hex, 16).map_err(|_| Error::UserError(format!("Invalid memory reference: {}", memory_reference)))
+}
+
+// Matches the call-instruction mnemonics of the architectures LLDB commonly disassembles for
+// (x86/x86-64 "call*", ARM/AArch64 "bl"/"blx"), case-insensitively. Conditional ARM forms with a
+// condition-code suffix (e.g. "bleq") are not matched.
+fn is_call_mnemonic(mnemonic: &str) -> bool {
+    let mnemonic = mnemonic.to_ascii_lowercase();
+    // ARM/AArch64 calls are "bl"/"blx" exactly; a bare `starts_with("bl")` also catches unrelated
+    // conditional branches that happen to share the prefix, e.g. "blt"/"ble"/"bls".
+    mnemonic.starts_with("call") || mnemonic == "bl" || mnemonic == "blx"
+}
+
+// Truncates `s` to at most `max_length` chars (on a char boundary, unlike a raw byte truncation),
+// appending ", ..." when it had to cut anything.
+fn truncate_with_ellipsis(mut s: String, max_length: usize) -> String {
+    if s.chars().count() > max_length {
+        let cut = s.char_indices().nth(max_length).map(|(i, _)| i).unwrap_or_else(|| s.len());
+        s.truncate(cut);
+        s.push_str(", ...");
+    }
+    s
+}
+
+// A trailing subscript that LLDB's own expression parser can't evaluate directly.
+enum Subscript {
+    Index(i64),
+    Slice(Option<i64>, Option<i64>),
+}
+
+// Detects a trailing `[...]` on `expr` that needs our own handling: a negative index (`foo[-1]`)
+// or a slice (`foo[a:b]`, with either bound optional). A plain non-negative index (`foo[2]`) is
+// left alone, since LLDB evaluates those natively. Returns the base expression and the subscript.
+fn parse_trailing_subscript(expr: &str) -> Option<(&str, Subscript)> {
+    let expr = expr.trim_end();
+    if !expr.ends_with(']') {
+        return None;
+    }
+    let open = expr.rfind('[')?;
+    let base = expr[..open].trim_end();
+    if base.is_empty() {
+        return None;
+    }
+    let inside = &expr[open + 1..expr.len() - 1];
+    if let Some(colon) = inside.find(':') {
+        let parse_bound = |s: &str| -> Option<Option<i64>> {
+            let s = s.trim();
+            if s.is_empty() {
+                Some(None)
+            } else {
+                s.parse().ok().map(Some)
+            }
+        };
+        let start = parse_bound(&inside[..colon])?;
+        let end = parse_bound(&inside[colon + 1..])?;
+        Some((base, Subscript::Slice(start, end)))
+    } else {
+        let index: i64 = inside.trim().parse().ok()?;
+        if index < 0 {
+            Some((base, Subscript::Index(index)))
+        } else {
+            None
+        }
+    }
+}
+
+// Resolves a (possibly negative) index against `count` children, Python-slice style.
+fn resolve_index(count: u32, index: i64) -> Option<u32> {
+    let resolved = if index < 0 { index + count as i64 } else { index };
+    if resolved >= 0 && resolved < count as i64 {
+        Some(resolved as u32)
+    } else {
+        None
+    }
+}
+
+// Resolves a (possibly negative, possibly open-ended) `start:end` slice against `count` children,
+// clamping both bounds into range the way Python's slicing does.
+fn resolve_slice_bounds(count: u32, start: Option<i64>, end: Option<i64>) -> Option<(u32, u32)> {
+    let resolve = |v: i64| if v < 0 { (v + count as i64).max(0) } else { v };
+    let start = start.map(resolve).unwrap_or(0).min(count as i64) as u32;
+    let end = end.map(resolve).unwrap_or(count as i64).min(count as i64) as u32;
+    if start <= end {
+        Some((start, end))
+    } else {
+        None
+    }
+}
+
+// Parses a dataId of the form produced by `handle_data_breakpoint_info`: "<hex address>/<size>".
+fn parse_data_id(data_id: &str) -> Option<(u64, u32)> {
+    let mut parts = data_id.splitn(2, '/');
+    let address = u64::from_str_radix(parts.next()?, 16).ok()?;
+    let size = parts.next()?.parse().ok()?;
+    Some((address, size))
+}
+
 fn compose_eval_name<'a, 'b, A, B>(prefix: A, suffix: B) -> String
 where
     A: Into<Cow<'a, str>>,
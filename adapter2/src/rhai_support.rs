@@ -0,0 +1,100 @@
+//! Rhai-backed expression evaluator, offered as `ExprType::Rhai` (the `/rhai ` prefix) so
+//! conditional breakpoints, watch expressions, and REPL `?`-expressions keep working on builds
+//! where the embedded Python interpreter isn't available. A fresh sandboxed `Engine` is built
+//! per evaluation, pre-populated with the stopped frame's locals and a handful of functions that
+//! bridge back into LLDB through a `SBValue` wrapper.
+
+use rhai::{Dynamic, Engine, Scope};
+
+use crate::lldb::{DynamicValueType, SBFrame, SBValue, VariableOptions};
+use crate::python::PythonValue;
+
+// Rhai's custom-type registry needs an owned, `Clone`-able wrapper around `SBValue` to expose it
+// to scripts as `var(name).child("foo").as_int()`-style chains.
+#[derive(Clone)]
+struct RhaiValue(SBValue);
+
+pub fn evaluate(frame: &SBFrame, expr: &str) -> Result<PythonValue, String> {
+    let mut engine = Engine::new();
+    register_value_api(&mut engine);
+    register_frame_api(&mut engine, frame.clone());
+
+    let mut scope = Scope::new();
+    let variables = frame.variables(&VariableOptions {
+        arguments: true,
+        locals: true,
+        statics: false,
+        in_scope_only: true,
+        use_dynamic: DynamicValueType::NoDynamicValues,
+    });
+    for var in variables.iter() {
+        if let Some(name) = var.name() {
+            scope.push(name.to_owned(), RhaiValue(var));
+        }
+    }
+
+    let result: Dynamic = engine.eval_with_scope(&mut scope, expr).map_err(|err| err.to_string())?;
+    Ok(dynamic_to_python_value(result))
+}
+
+// Evaluates `expr` against a single `SBValue` bound to `$`, with no frame in scope - used by
+// scripted summary providers (see `DebugSession::render_summary_script`), which run outside any
+// particular stack frame.
+pub fn evaluate_value(value: &SBValue, expr: &str) -> Result<PythonValue, String> {
+    let mut engine = Engine::new();
+    register_value_api(&mut engine);
+
+    let mut scope = Scope::new();
+    scope.push("$", RhaiValue(value.clone()));
+
+    let result: Dynamic = engine.eval_with_scope(&mut scope, expr).map_err(|err| err.to_string())?;
+    Ok(dynamic_to_python_value(result))
+}
+
+// The `SBValue`-only half of the API, shared by both the frame-scoped evaluator and the
+// frame-less one used by summary provider scripts.
+fn register_value_api(engine: &mut Engine) {
+    engine.register_type_with_name::<RhaiValue>("SBValue");
+    engine.register_get("value", |v: &mut RhaiValue| sbvalue_string(&v.0));
+    engine.register_get("type_name", |v: &mut RhaiValue| v.0.type_name().unwrap_or("").to_owned());
+    engine.register_get("num_children", |v: &mut RhaiValue| v.0.num_children() as i64);
+    engine.register_indexer_get(|v: &mut RhaiValue, name: &str| RhaiValue(v.0.child_member_with_name(name)));
+    engine.register_indexer_get(|v: &mut RhaiValue, i: i64| RhaiValue(v.0.child_at_index(i as u32)));
+
+    engine.register_fn("deref", |v: &mut RhaiValue| RhaiValue(v.0.dereference()));
+    engine.register_fn("as_int", |v: &mut RhaiValue| v.0.try_value_as_unsigned().unwrap_or(0) as i64);
+    engine.register_fn("as_str", |v: &mut RhaiValue| sbvalue_string(&v.0));
+    engine.register_fn("summary", |v: &mut RhaiValue| v.0.summary().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default());
+    engine.register_fn("child", |v: &mut RhaiValue, name: &str| RhaiValue(v.0.child_member_with_name(name)));
+    engine.register_fn("index", |v: &mut RhaiValue, i: i64| RhaiValue(v.0.child_at_index(i as u32)));
+}
+
+// The remaining functions that need a stopped frame (`var(name)`, `read_mem`), registered only
+// for `evaluate`.
+fn register_frame_api(engine: &mut Engine, frame: SBFrame) {
+    let var_frame = frame.clone();
+    engine.register_fn("var", move |name: &str| RhaiValue(var_frame.find_variable(name)));
+
+    engine.register_fn("read_mem", move |addr: i64, len: i64| -> Vec<Dynamic> {
+        let process = frame.thread().process();
+        let mut buffer = vec![0u8; len.max(0) as usize];
+        let (bytes_read, _) = process.read_memory(addr as u64, &mut buffer);
+        buffer[..bytes_read].iter().map(|b| Dynamic::from(*b as i64)).collect()
+    });
+}
+
+fn sbvalue_string(val: &SBValue) -> String {
+    val.value().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default()
+}
+
+fn dynamic_to_python_value(value: Dynamic) -> PythonValue {
+    if value.is::<RhaiValue>() {
+        PythonValue::SBValue(value.cast::<RhaiValue>().0)
+    } else if value.is::<bool>() {
+        PythonValue::Bool(value.cast::<bool>())
+    } else if value.is::<i64>() {
+        PythonValue::Int(value.cast::<i64>())
+    } else {
+        PythonValue::String(value.to_string())
+    }
+}